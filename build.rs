@@ -0,0 +1,68 @@
+//! Derives the available ports and peripherals from the chip and package features.
+//!
+//! The result is passed to the compiler as `has_*` cfg flags, so that code that uses a port or
+//! peripheral that the selected chip does not have fails to build.
+
+use std::env;
+
+/// Chip features and the peripherals that only some chips have
+const CHIPS: &'static [(&'static str, &'static [&'static str])] = &[
+    ("stm32f722", &["sdmmc2"]),
+    ("stm32f723", &["sdmmc2"]),
+    ("stm32f732", &["sdmmc2", "cryp"]),
+    ("stm32f733", &["sdmmc2", "cryp"]),
+    ("stm32f745", &["gpio_j", "gpio_k", "eth", "dma2d", "can2", "dcmi", "spdifrx", "i2c4", "cec",
+                    "spi6"]),
+    ("stm32f746", &["gpio_j", "gpio_k", "eth", "dma2d", "can2", "dcmi", "spdifrx", "i2c4", "cec",
+                    "spi6", "ltdc"]),
+    ("stm32f756", &["gpio_j", "gpio_k", "eth", "dma2d", "can2", "dcmi", "spdifrx", "i2c4", "cec",
+                    "spi6", "ltdc", "cryp", "hash"]),
+    ("stm32f765", &["gpio_j", "gpio_k", "eth", "dma2d", "can2", "dcmi", "spdifrx", "i2c4", "cec",
+                    "spi6", "can3", "sdmmc2", "dfsdm1", "mdio"]),
+    ("stm32f767", &["gpio_j", "gpio_k", "eth", "dma2d", "can2", "dcmi", "spdifrx", "i2c4", "cec",
+                    "spi6", "can3", "sdmmc2", "dfsdm1", "mdio", "ltdc", "jpeg"]),
+    ("stm32f769", &["gpio_j", "gpio_k", "eth", "dma2d", "can2", "dcmi", "spdifrx", "i2c4", "cec",
+                    "spi6", "can3", "sdmmc2", "dfsdm1", "mdio", "ltdc", "jpeg", "dsi"]),
+    ("stm32f777", &["gpio_j", "gpio_k", "eth", "dma2d", "can2", "dcmi", "spdifrx", "i2c4", "cec",
+                    "spi6", "can3", "sdmmc2", "dfsdm1", "mdio", "ltdc", "jpeg", "cryp", "hash"]),
+    ("stm32f779", &["gpio_j", "gpio_k", "eth", "dma2d", "can2", "dcmi", "spdifrx", "i2c4", "cec",
+                    "spi6", "can3", "sdmmc2", "dfsdm1", "mdio", "ltdc", "jpeg", "dsi", "cryp",
+                    "hash"]),
+];
+
+/// Package features and the ports that they lack
+const PACKAGES: &'static [(&'static str, &'static [&'static str])] = &[
+    ("package-64", &["gpio_e", "gpio_f", "gpio_g", "gpio_i", "gpio_j", "gpio_k"]),
+    ("package-100", &["gpio_f", "gpio_g", "gpio_i", "gpio_j", "gpio_k"]),
+    ("package-144", &["gpio_i", "gpio_j", "gpio_k"]),
+    ("package-176", &["gpio_j", "gpio_k"]),
+];
+
+fn feature_enabled(feature: &str) -> bool {
+    let var = format!("CARGO_FEATURE_{}", feature.to_uppercase().replace("-", "_"));
+    env::var_os(var).is_some()
+}
+
+fn main() {
+    let chips: Vec<_> = CHIPS.iter().filter(|chip| feature_enabled(chip.0)).collect();
+    let (chip, peripherals) = match chips.len() {
+        // the STM32F746 was the only supported chip before the chip features were introduced
+        0 => ("stm32f746", CHIPS.iter().find(|chip| chip.0 == "stm32f746").unwrap().1),
+        1 => (chips[0].0, chips[0].1),
+        _ => panic!("more than one chip feature selected"),
+    };
+
+    let packages: Vec<_> = PACKAGES.iter().filter(|package| feature_enabled(package.0)).collect();
+    if packages.len() > 1 {
+        panic!("more than one package feature selected");
+    }
+    let missing = packages.first().map(|package| package.1).unwrap_or(&[]);
+
+    println!("cargo:rustc-cfg={}", chip);
+    let common = ["gpio_e", "gpio_f", "gpio_g", "gpio_i"];
+    for peripheral in common.iter().chain(peripherals.iter()) {
+        if !missing.contains(peripheral) {
+            println!("cargo:rustc-cfg=has_{}", peripheral);
+        }
+    }
+}
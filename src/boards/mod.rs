@@ -0,0 +1,5 @@
+//! Board support
+
+// the discovery board uses the STM32F746NG in a 216-pin package
+#[cfg(all(stm32f746, has_gpio_k))]
+pub mod stm32f7_discovery;
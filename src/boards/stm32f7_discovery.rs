@@ -0,0 +1,271 @@
+//! STM32F746G Discovery board
+//!
+//! Provides the items the `board!` macro expands to.
+
+use InterruptHandler;
+
+/// The register banks of the board
+pub use peripherals::Peripherals as Hardware;
+
+/// Frequency of the CPU after reset, which runs from the internal 16 MHz oscillator (HSI)
+pub const INITIAL_CPU_FREQ: u32 = 16_000_000;
+
+/// Returns all register banks of the board.
+///
+/// The `board!` macro calls this once and passes the result to `main`, so `Peripherals::take`
+/// returns `None` afterwards. Panics if the banks were already taken.
+pub unsafe fn hw() -> Hardware {
+    Hardware::take().expect("register banks already taken")
+}
+
+/// Vector table of the STM32F746
+///
+/// Unused entries are `None`, which is stored as zero.
+#[repr(C)]
+pub struct VectorTable {
+    /// Initial value of the main stack pointer
+    pub msp: &'static (),
+    /// Reset handler
+    pub reset: Option<extern "C" fn()>,
+    /// Non-maskable interrupt
+    pub nmi: Option<InterruptHandler>,
+    /// Hard fault
+    pub hard_fault: Option<InterruptHandler>,
+    /// Memory management fault
+    pub mem_manage: Option<InterruptHandler>,
+    /// Bus fault
+    pub bus_fault: Option<InterruptHandler>,
+    /// Usage fault
+    pub usage_fault: Option<InterruptHandler>,
+    pub _reserved1: Option<InterruptHandler>,
+    pub _reserved2: Option<InterruptHandler>,
+    pub _reserved3: Option<InterruptHandler>,
+    pub _reserved4: Option<InterruptHandler>,
+    /// Supervisor call
+    pub svcall: Option<InterruptHandler>,
+    /// Debug monitor
+    pub debug_monitor: Option<InterruptHandler>,
+    pub _reserved5: Option<InterruptHandler>,
+    /// Pendable request for system service
+    pub pendsv: Option<InterruptHandler>,
+    /// System tick timer
+    pub systick: Option<InterruptHandler>,
+
+    // device specific interrupts (IRQ 0 to 97)
+    pub wwdg: Option<InterruptHandler>,
+    pub pvd: Option<InterruptHandler>,
+    pub tamp_stamp: Option<InterruptHandler>,
+    pub rtc_wkup: Option<InterruptHandler>,
+    pub flash: Option<InterruptHandler>,
+    pub rcc: Option<InterruptHandler>,
+    pub exti0: Option<InterruptHandler>,
+    pub exti1: Option<InterruptHandler>,
+    pub exti2: Option<InterruptHandler>,
+    pub exti3: Option<InterruptHandler>,
+    pub exti4: Option<InterruptHandler>,
+    pub dma1_stream0: Option<InterruptHandler>,
+    pub dma1_stream1: Option<InterruptHandler>,
+    pub dma1_stream2: Option<InterruptHandler>,
+    pub dma1_stream3: Option<InterruptHandler>,
+    pub dma1_stream4: Option<InterruptHandler>,
+    pub dma1_stream5: Option<InterruptHandler>,
+    pub dma1_stream6: Option<InterruptHandler>,
+    pub adc: Option<InterruptHandler>,
+    pub can1_tx: Option<InterruptHandler>,
+    pub can1_rx0: Option<InterruptHandler>,
+    pub can1_rx1: Option<InterruptHandler>,
+    pub can1_sce: Option<InterruptHandler>,
+    pub exti9_5: Option<InterruptHandler>,
+    pub tim1_brk_tim9: Option<InterruptHandler>,
+    pub tim1_up_tim10: Option<InterruptHandler>,
+    pub tim1_trg_com_tim11: Option<InterruptHandler>,
+    pub tim1_cc: Option<InterruptHandler>,
+    pub tim2: Option<InterruptHandler>,
+    pub tim3: Option<InterruptHandler>,
+    pub tim4: Option<InterruptHandler>,
+    pub i2c1_ev: Option<InterruptHandler>,
+    pub i2c1_er: Option<InterruptHandler>,
+    pub i2c2_ev: Option<InterruptHandler>,
+    pub i2c2_er: Option<InterruptHandler>,
+    pub spi1: Option<InterruptHandler>,
+    pub spi2: Option<InterruptHandler>,
+    pub usart1: Option<InterruptHandler>,
+    pub usart2: Option<InterruptHandler>,
+    pub usart3: Option<InterruptHandler>,
+    pub exti15_10: Option<InterruptHandler>,
+    pub rtc_alarm: Option<InterruptHandler>,
+    pub otg_fs_wkup: Option<InterruptHandler>,
+    pub tim8_brk_tim12: Option<InterruptHandler>,
+    pub tim8_up_tim13: Option<InterruptHandler>,
+    pub tim8_trg_com_tim14: Option<InterruptHandler>,
+    pub tim8_cc: Option<InterruptHandler>,
+    pub dma1_stream7: Option<InterruptHandler>,
+    pub fmc: Option<InterruptHandler>,
+    pub sdmmc1: Option<InterruptHandler>,
+    pub tim5: Option<InterruptHandler>,
+    pub spi3: Option<InterruptHandler>,
+    pub uart4: Option<InterruptHandler>,
+    pub uart5: Option<InterruptHandler>,
+    pub tim6_dac: Option<InterruptHandler>,
+    pub tim7: Option<InterruptHandler>,
+    pub dma2_stream0: Option<InterruptHandler>,
+    pub dma2_stream1: Option<InterruptHandler>,
+    pub dma2_stream2: Option<InterruptHandler>,
+    pub dma2_stream3: Option<InterruptHandler>,
+    pub dma2_stream4: Option<InterruptHandler>,
+    pub eth: Option<InterruptHandler>,
+    pub eth_wkup: Option<InterruptHandler>,
+    pub can2_tx: Option<InterruptHandler>,
+    pub can2_rx0: Option<InterruptHandler>,
+    pub can2_rx1: Option<InterruptHandler>,
+    pub can2_sce: Option<InterruptHandler>,
+    pub otg_fs: Option<InterruptHandler>,
+    pub dma2_stream5: Option<InterruptHandler>,
+    pub dma2_stream6: Option<InterruptHandler>,
+    pub dma2_stream7: Option<InterruptHandler>,
+    pub usart6: Option<InterruptHandler>,
+    pub i2c3_ev: Option<InterruptHandler>,
+    pub i2c3_er: Option<InterruptHandler>,
+    pub otg_hs_ep1_out: Option<InterruptHandler>,
+    pub otg_hs_ep1_in: Option<InterruptHandler>,
+    pub otg_hs_wkup: Option<InterruptHandler>,
+    pub otg_hs: Option<InterruptHandler>,
+    pub dcmi: Option<InterruptHandler>,
+    pub cryp: Option<InterruptHandler>,
+    pub hash_rng: Option<InterruptHandler>,
+    pub fpu: Option<InterruptHandler>,
+    pub uart7: Option<InterruptHandler>,
+    pub uart8: Option<InterruptHandler>,
+    pub spi4: Option<InterruptHandler>,
+    pub spi5: Option<InterruptHandler>,
+    pub spi6: Option<InterruptHandler>,
+    pub sai1: Option<InterruptHandler>,
+    pub ltdc: Option<InterruptHandler>,
+    pub ltdc_er: Option<InterruptHandler>,
+    pub dma2d: Option<InterruptHandler>,
+    pub sai2: Option<InterruptHandler>,
+    pub quadspi: Option<InterruptHandler>,
+    pub lptim1: Option<InterruptHandler>,
+    pub cec: Option<InterruptHandler>,
+    pub i2c4_ev: Option<InterruptHandler>,
+    pub i2c4_er: Option<InterruptHandler>,
+    pub spdif_rx: Option<InterruptHandler>,
+}
+
+/// Vector table with no handlers, to be completed by the `board!` macro
+pub const VECTOR_TABLE: VectorTable = VectorTable {
+    msp: &(),
+    reset: None,
+    nmi: None,
+    hard_fault: None,
+    mem_manage: None,
+    bus_fault: None,
+    usage_fault: None,
+    _reserved1: None,
+    _reserved2: None,
+    _reserved3: None,
+    _reserved4: None,
+    svcall: None,
+    debug_monitor: None,
+    _reserved5: None,
+    pendsv: None,
+    systick: None,
+    wwdg: None,
+    pvd: None,
+    tamp_stamp: None,
+    rtc_wkup: None,
+    flash: None,
+    rcc: None,
+    exti0: None,
+    exti1: None,
+    exti2: None,
+    exti3: None,
+    exti4: None,
+    dma1_stream0: None,
+    dma1_stream1: None,
+    dma1_stream2: None,
+    dma1_stream3: None,
+    dma1_stream4: None,
+    dma1_stream5: None,
+    dma1_stream6: None,
+    adc: None,
+    can1_tx: None,
+    can1_rx0: None,
+    can1_rx1: None,
+    can1_sce: None,
+    exti9_5: None,
+    tim1_brk_tim9: None,
+    tim1_up_tim10: None,
+    tim1_trg_com_tim11: None,
+    tim1_cc: None,
+    tim2: None,
+    tim3: None,
+    tim4: None,
+    i2c1_ev: None,
+    i2c1_er: None,
+    i2c2_ev: None,
+    i2c2_er: None,
+    spi1: None,
+    spi2: None,
+    usart1: None,
+    usart2: None,
+    usart3: None,
+    exti15_10: None,
+    rtc_alarm: None,
+    otg_fs_wkup: None,
+    tim8_brk_tim12: None,
+    tim8_up_tim13: None,
+    tim8_trg_com_tim14: None,
+    tim8_cc: None,
+    dma1_stream7: None,
+    fmc: None,
+    sdmmc1: None,
+    tim5: None,
+    spi3: None,
+    uart4: None,
+    uart5: None,
+    tim6_dac: None,
+    tim7: None,
+    dma2_stream0: None,
+    dma2_stream1: None,
+    dma2_stream2: None,
+    dma2_stream3: None,
+    dma2_stream4: None,
+    eth: None,
+    eth_wkup: None,
+    can2_tx: None,
+    can2_rx0: None,
+    can2_rx1: None,
+    can2_sce: None,
+    otg_fs: None,
+    dma2_stream5: None,
+    dma2_stream6: None,
+    dma2_stream7: None,
+    usart6: None,
+    i2c3_ev: None,
+    i2c3_er: None,
+    otg_hs_ep1_out: None,
+    otg_hs_ep1_in: None,
+    otg_hs_wkup: None,
+    otg_hs: None,
+    dcmi: None,
+    cryp: None,
+    hash_rng: None,
+    fpu: None,
+    uart7: None,
+    uart8: None,
+    spi4: None,
+    spi5: None,
+    spi6: None,
+    sai1: None,
+    ltdc: None,
+    ltdc_er: None,
+    dma2d: None,
+    sai2: None,
+    quadspi: None,
+    lptim1: None,
+    cec: None,
+    i2c4_ev: None,
+    i2c4_er: None,
+    spdif_rx: None,
+};
@@ -0,0 +1,25 @@
+//! EXTI line registers (EXTI_IMR, EXTI_EMR, EXTI_RTSR, EXTI_FTSR, EXTI_SWIER and EXTI_PR)
+
+use bit_field::BitField;
+
+/// Register with one bit per EXTI line (lines 0 to 23)
+#[derive(Debug, Clone, Copy)]
+pub struct Register(BitField<u32>);
+
+impl Register {
+    pub fn get(&self, line: u8) -> bool {
+        assert!(line < 24);
+        self.0.get_bit(line)
+    }
+
+    pub fn set(&mut self, line: u8, value: bool) {
+        assert!(line < 24);
+        self.0.set_bit(line, value);
+    }
+}
+
+impl Default for Register {
+    fn default() -> Register {
+        Register(BitField::new(0))
+    }
+}
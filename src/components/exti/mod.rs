@@ -0,0 +1,26 @@
+//! Extended interrupts and events controller (EXTI)
+
+use volatile::Volatile;
+
+pub mod lines;
+
+#[repr(C)]
+pub struct ExtiBank {
+    /// Interrupt mask register
+    pub imr: Volatile<lines::Register>,
+    /// Event mask register
+    pub emr: Volatile<lines::Register>,
+    /// Rising trigger selection register
+    pub rtsr: Volatile<lines::Register>,
+    /// Falling trigger selection register
+    pub ftsr: Volatile<lines::Register>,
+
+    // 0x10
+    /// Software interrupt event register
+    pub swier: Volatile<lines::Register>,
+    /// Pending register
+    ///
+    /// Pending bits are cleared by writing 1, so only write registers that have exactly the bits
+    /// to clear set instead of using `update`.
+    pub pr: Volatile<lines::Register>,
+}
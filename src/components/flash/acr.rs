@@ -10,6 +10,6 @@ impl Register {
 
     pub fn set_latency(&mut self, latency: u32) {
         assert!(latency < 16);
-        self.0 |= latency;
+        self.0 = (self.0 & !0b1111) | latency;
     }
 }
@@ -12,6 +12,16 @@ pub struct AlternateFunctionRegister {
 }
 
 impl AlternateFunctionRegister {
+    /// Returns the alternate function of `pin`
+    pub fn get(&self, pin: Pin) -> AlternateFunction {
+        let value = if (pin as u8) < 8 {
+            self.low.get(pin)
+        } else {
+            self.high.get(pin)
+        };
+        AlternateFunction::from_bits(value)
+    }
+
     /// Sets `pin`
     pub fn set(&mut self, pin: Pin, alternate_fn: AlternateFunction) {
         self.low.set(pin, alternate_fn);
@@ -19,7 +29,7 @@ impl AlternateFunctionRegister {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum AlternateFunction {
     AF0 = 0b0000,
@@ -40,12 +50,48 @@ pub enum AlternateFunction {
     AF15 = 0b1111,
 }
 
+impl AlternateFunction {
+    /// Returns the number of the alternate function, e.g. 7 for `AF7`
+    pub fn number(&self) -> u8 {
+        *self as u8
+    }
+
+    fn from_bits(value: u32) -> AlternateFunction {
+        use self::AlternateFunction::*;
+        match value {
+            0b0000 => AF0,
+            0b0001 => AF1,
+            0b0010 => AF2,
+            0b0011 => AF3,
+            0b0100 => AF4,
+            0b0101 => AF5,
+            0b0110 => AF6,
+            0b0111 => AF7,
+            0b1000 => AF8,
+            0b1001 => AF9,
+            0b1010 => AF10,
+            0b1011 => AF11,
+            0b1100 => AF12,
+            0b1101 => AF13,
+            0b1110 => AF14,
+            0b1111 => AF15,
+            _ => unreachable!(),
+        }
+    }
+}
+
 
 #[derive(Clone, Copy)]
 #[repr(C)]
 struct High(BitField<u32>);
 
 impl High {
+    /// Returns the alternate function bits of the given high pin
+    pub fn get(&self, pin: Pin) -> u32 {
+        let offset = (pin as u8 - 8) * 4;
+        self.0.get_range(offset..(offset + 4))
+    }
+
     /// Sets the alternate function for the given high pins
     pub fn set(&mut self, pin: Pin, alternate_fn: AlternateFunction) {
         let pin_number = pin as u8;
@@ -61,6 +107,12 @@ impl High {
 struct Low(BitField<u32>);
 
 impl Low {
+    /// Returns the alternate function bits of the given low pin
+    pub fn get(&self, pin: Pin) -> u32 {
+        let offset = pin as u8 * 4;
+        self.0.get_range(offset..(offset + 4))
+    }
+
     /// Sets the alternate function for the given low pins
     pub fn set(&mut self, pin: Pin, alternate_fn: AlternateFunction) {
         let pin_number = pin as u8;
@@ -0,0 +1,38 @@
+//! GPIO port configuration lock register (GPIOx_LCKR)
+
+use super::Pin;
+use bit_field::BitField;
+
+/// Register
+#[derive(Clone, Copy)]
+pub struct LockRegister(BitField<u32>);
+
+impl LockRegister {
+    /// Returns whether the configuration of `pin` is locked (LCKy)
+    pub fn locked(&self, pin: Pin) -> bool {
+        self.0.get_bit(pin as u8)
+    }
+
+    /// Selects `pin` for the lock key write sequence (LCKy)
+    pub fn set_locked(&mut self, pin: Pin, value: bool) {
+        self.0.set_bit(pin as u8, value);
+    }
+
+    /// Returns whether the lock key is active (LCKK)
+    ///
+    /// Once active, the register is frozen until the next MCU or peripheral reset.
+    pub fn lock_key(&self) -> bool {
+        self.0.get_bit(16)
+    }
+
+    /// Sets the lock key bit (LCKK) for the lock key write sequence
+    pub fn set_lock_key(&mut self, value: bool) {
+        self.0.set_bit(16, value);
+    }
+}
+
+impl Default for LockRegister {
+    fn default() -> LockRegister {
+        LockRegister(BitField::new(0))
+    }
+}
@@ -8,6 +8,7 @@ mod out_speed;
 mod out_type;
 mod resistor;
 mod alternate_fn;
+mod lock;
 
 pub use self::mode::{ModeRegister, Mode};
 pub use self::input_data::InputDataRegister;
@@ -17,6 +18,7 @@ pub use self::out_type::{OutputTypeRegister, OutputType};
 pub use self::out_speed::{OutputSpeedRegister, OutputSpeed};
 pub use self::resistor::{ResistorRegister, Resistor};
 pub use self::alternate_fn::{AlternateFunctionRegister, AlternateFunction};
+pub use self::lock::LockRegister;
 
 use volatile::{ReadOnly, WriteOnly, ReadWrite};
 
@@ -31,7 +33,7 @@ pub struct Gpio {
     pub input_data: ReadOnly<input_data::InputDataRegister>,
     pub output_data: ReadOnly<output_data::OutputDataRegister>,
     pub bit_set_reset: WriteOnly<bit_set_reset::BitSetResetRegister>,
-    pub lckr: ReadWrite<u32>,
+    pub lckr: ReadWrite<lock::LockRegister>,
 
     // 0x20
     pub alternate_fn: ReadWrite<alternate_fn::AlternateFunctionRegister>,
@@ -8,6 +8,18 @@ use bit_field::BitField;
 pub struct ModeRegister(BitField<u32>);
 
 impl ModeRegister {
+    /// Returns the mode of the given pin
+    pub fn get(&self, pin: Pin) -> Mode {
+        let offset = (pin as u8) * 2;
+        match self.0.get_range(offset..offset + 2) {
+            0b00 => Mode::Input,
+            0b01 => Mode::Output,
+            0b10 => Mode::AlternateFunction,
+            0b11 => Mode::Analog,
+            _ => unreachable!(),
+        }
+    }
+
     /// Sets the mode for the given pins
     pub fn set(&mut self, pin: Pin, mode: Mode) {
         let offset = (pin as u8) * 2;
@@ -15,7 +27,7 @@ impl ModeRegister {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum Mode {
     Input = 0b00,
@@ -8,14 +8,26 @@ use bit_field::BitField;
 pub struct OutputSpeedRegister(BitField<u32>);
 
 impl OutputSpeedRegister {
-    /// Returns the output speed for the given pins
+    /// Returns the output speed of the given pin
+    pub fn get(&self, pin: Pin) -> OutputSpeed {
+        let offset = (pin as u8) * 2;
+        match self.0.get_range(offset..offset + 2) {
+            0b00 => OutputSpeed::Low,
+            0b01 => OutputSpeed::Medium,
+            0b10 => OutputSpeed::High,
+            0b11 => OutputSpeed::VeryHigh,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Sets the output speed for the given pins
     pub fn set(&mut self, pin: Pin, speed: OutputSpeed) {
         let offset = (pin as u8) * 2;
         self.0.set_range(offset..offset + 2, speed as u32);
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum OutputSpeed {
     Low = 0b00,
@@ -8,6 +8,15 @@ use bit_field::BitField;
 pub struct OutputTypeRegister(BitField<u32>);
 
 impl OutputTypeRegister {
+    /// Returns the output type of the given pin
+    pub fn get(&self, pin: Pin) -> OutputType {
+        if self.0.get_bit(pin as u8) {
+            OutputType::OpenDrain
+        } else {
+            OutputType::PushPull
+        }
+    }
+
     /// Sets the output type for the given pins
     pub fn set(&mut self, pin: Pin, typ: OutputType) {
         let offset = pin as u8;
@@ -15,7 +24,7 @@ impl OutputTypeRegister {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum OutputType {
     PushPull = 0,
@@ -8,6 +8,18 @@ use bit_field::BitField;
 pub struct ResistorRegister(BitField<u32>);
 
 impl ResistorRegister {
+    /// Returns the resistor of the given pin, or `None` for the reserved value
+    pub fn get(&self, pin: Pin) -> Option<Resistor> {
+        let offset = (pin as u8) * 2;
+        match self.0.get_range(offset..offset + 2) {
+            0b00 => Some(Resistor::NoPull),
+            0b01 => Some(Resistor::PullUp),
+            0b10 => Some(Resistor::PullDown),
+            0b11 => None,
+            _ => unreachable!(),
+        }
+    }
+
     /// Sets the resistor for the given pins
     pub fn set(&mut self, pin: Pin, resistor: Resistor) {
         let offset = (pin as u8) * 2;
@@ -15,7 +27,7 @@ impl ResistorRegister {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum Resistor {
     NoPull = 0b00,
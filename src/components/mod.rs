@@ -3,3 +3,5 @@ pub mod systick;
 pub mod rcc;
 pub mod pwr;
 pub mod flash;
+pub mod syscfg;
+pub mod exti;
@@ -6,12 +6,18 @@ bitflags! {
         const GPIO_B_ENABLE = 1 << 1,
         const GPIO_C_ENABLE = 1 << 2,
         const GPIO_D_ENABLE = 1 << 3,
+        #[cfg(has_gpio_e)]
         const GPIO_E_ENABLE = 1 << 4,
+        #[cfg(has_gpio_f)]
         const GPIO_F_ENABLE = 1 << 5,
+        #[cfg(has_gpio_g)]
         const GPIO_G_ENABLE = 1 << 6,
         const GPIO_H_ENABLE = 1 << 7,
+        #[cfg(has_gpio_i)]
         const GPIO_I_ENABLE = 1 << 8,
+        #[cfg(has_gpio_j)]
         const GPIO_J_ENABLE = 1 << 9,
+        #[cfg(has_gpio_k)]
         const GPIO_K_ENABLE = 1 << 10,
 
         const CRC_ENABLE = 1 << 12,
@@ -19,10 +25,15 @@ bitflags! {
         const DTCMRAM_ENABLE = 1 << 20,
         const DMA1_ENABLE = 1 << 21,
         const DMA2_ENABLE = 1 << 22,
+        #[cfg(has_dma2d)]
         const DMA2D_ENABLE = 1 << 23,
+        #[cfg(has_eth)]
         const ETHMAC_ENABLE = 1 << 25,
+        #[cfg(has_eth)]
         const ETHMAC_TX_ENABLE = 1 << 26,
+        #[cfg(has_eth)]
         const ETHMAC_RX_ENABLE = 1 << 27,
+        #[cfg(has_eth)]
         const ETHMAC_PTP_ENABLE = 1 << 28,
         const OTG_HS_ENABLE = 1 << 29,
         const OTG_HSULPI_ENABLE = 1 << 30,
@@ -0,0 +1,45 @@
+//! RCC AHB1 peripheral clock enable in low power mode register (RCC_AHB1LPENR)
+
+bitflags! {
+    pub flags Register: u32 {
+        const GPIO_A_LP_ENABLE = 1 << 0,
+        const GPIO_B_LP_ENABLE = 1 << 1,
+        const GPIO_C_LP_ENABLE = 1 << 2,
+        const GPIO_D_LP_ENABLE = 1 << 3,
+        #[cfg(has_gpio_e)]
+        const GPIO_E_LP_ENABLE = 1 << 4,
+        #[cfg(has_gpio_f)]
+        const GPIO_F_LP_ENABLE = 1 << 5,
+        #[cfg(has_gpio_g)]
+        const GPIO_G_LP_ENABLE = 1 << 6,
+        const GPIO_H_LP_ENABLE = 1 << 7,
+        #[cfg(has_gpio_i)]
+        const GPIO_I_LP_ENABLE = 1 << 8,
+        #[cfg(has_gpio_j)]
+        const GPIO_J_LP_ENABLE = 1 << 9,
+        #[cfg(has_gpio_k)]
+        const GPIO_K_LP_ENABLE = 1 << 10,
+
+        const CRC_LP_ENABLE = 1 << 12,
+        const AXI_LP_ENABLE = 1 << 13,
+        const FLITF_LP_ENABLE = 1 << 15,
+        const SRAM_1_LP_ENABLE = 1 << 16,
+        const SRAM_2_LP_ENABLE = 1 << 17,
+        const BKPSRAM_LP_ENABLE = 1 << 18,
+        const DTCMRAM_LP_ENABLE = 1 << 20,
+        const DMA1_LP_ENABLE = 1 << 21,
+        const DMA2_LP_ENABLE = 1 << 22,
+        #[cfg(has_dma2d)]
+        const DMA2D_LP_ENABLE = 1 << 23,
+        #[cfg(has_eth)]
+        const ETHMAC_LP_ENABLE = 1 << 25,
+        #[cfg(has_eth)]
+        const ETHMAC_TX_LP_ENABLE = 1 << 26,
+        #[cfg(has_eth)]
+        const ETHMAC_RX_LP_ENABLE = 1 << 27,
+        #[cfg(has_eth)]
+        const ETHMAC_PTP_LP_ENABLE = 1 << 28,
+        const OTG_HS_LP_ENABLE = 1 << 29,
+        const OTG_HSULPI_LP_ENABLE = 1 << 30,
+    }
+}
@@ -6,14 +6,27 @@ bitflags! {
         const GPIO_B_RESET = 1 << 1,
         const GPIO_C_RESET = 1 << 2,
         const GPIO_D_RESET = 1 << 3,
+        #[cfg(has_gpio_e)]
         const GPIO_E_RESET = 1 << 4,
+        #[cfg(has_gpio_f)]
         const GPIO_F_RESET = 1 << 5,
+        #[cfg(has_gpio_g)]
         const GPIO_G_RESET = 1 << 6,
         const GPIO_H_RESET = 1 << 7,
+        #[cfg(has_gpio_i)]
         const GPIO_I_RESET = 1 << 8,
+        #[cfg(has_gpio_j)]
         const GPIO_J_RESET = 1 << 9,
+        #[cfg(has_gpio_k)]
         const GPIO_K_RESET = 1 << 10,
 
-        // etc
+        const CRC_RESET = 1 << 12,
+        const DMA1_RESET = 1 << 21,
+        const DMA2_RESET = 1 << 22,
+        #[cfg(has_dma2d)]
+        const DMA2D_RESET = 1 << 23,
+        #[cfg(has_eth)]
+        const ETHMAC_RESET = 1 << 25,
+        const OTG_HS_RESET = 1 << 29,
     }
 }
@@ -0,0 +1,16 @@
+//! RCC AHB2 peripheral clock enable register (RCC_AHB2ENR)
+
+bitflags! {
+    pub flags Register: u32 {
+        #[cfg(has_dcmi)]
+        const DCMI_ENABLE = 1 << 0,
+        #[cfg(has_jpeg)]
+        const JPEG_ENABLE = 1 << 1,
+        #[cfg(has_cryp)]
+        const CRYP_ENABLE = 1 << 4,
+        #[cfg(has_hash)]
+        const HASH_ENABLE = 1 << 5,
+        const RNG_ENABLE = 1 << 6,
+        const OTG_FS_ENABLE = 1 << 7,
+    }
+}
@@ -0,0 +1,16 @@
+//! RCC AHB2 peripheral clock enable in low power mode register (RCC_AHB2LPENR)
+
+bitflags! {
+    pub flags Register: u32 {
+        #[cfg(has_dcmi)]
+        const DCMI_LP_ENABLE = 1 << 0,
+        #[cfg(has_jpeg)]
+        const JPEG_LP_ENABLE = 1 << 1,
+        #[cfg(has_cryp)]
+        const CRYP_LP_ENABLE = 1 << 4,
+        #[cfg(has_hash)]
+        const HASH_LP_ENABLE = 1 << 5,
+        const RNG_LP_ENABLE = 1 << 6,
+        const OTG_FS_LP_ENABLE = 1 << 7,
+    }
+}
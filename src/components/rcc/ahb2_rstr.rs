@@ -0,0 +1,16 @@
+//! RCC AHB2 peripheral reset register (RCC_AHB2RSTR)
+
+bitflags! {
+    pub flags Register: u32 {
+        #[cfg(has_dcmi)]
+        const DCMI_RESET = 1 << 0,
+        #[cfg(has_jpeg)]
+        const JPEG_RESET = 1 << 1,
+        #[cfg(has_cryp)]
+        const CRYP_RESET = 1 << 4,
+        #[cfg(has_hash)]
+        const HASH_RESET = 1 << 5,
+        const RNG_RESET = 1 << 6,
+        const OTG_FS_RESET = 1 << 7,
+    }
+}
@@ -0,0 +1,8 @@
+//! RCC AHB3 peripheral clock enable in low power mode register (RCC_AHB3LPENR)
+
+bitflags! {
+    pub flags Register: u32 {
+        const FMC_LP_ENABLE = 1 << 0,
+        const QSPI_LP_ENABLE = 1 << 1,
+    }
+}
@@ -13,8 +13,11 @@ bitflags! {
         const TIM_14_ENABLE = 1 << 8,
         const LPTIM_1_ENABLE = 1 << 9,
         const WWDG_ENABLE = 1 << 11,
+        #[cfg(has_can3)]
+        const CAN_3_ENABLE = 1 << 13,
         const SPI_2_ENABLE = 1 << 14,
         const SPI_3_ENABLE = 1 << 15,
+        #[cfg(has_spdifrx)]
         const SPDIFRX_ENABLE = 1 << 16,
         const USART_2_ENABLE = 1 << 17,
         const USART_3_ENABLE = 1 << 18,
@@ -25,7 +28,9 @@ bitflags! {
         const I2C_3_ENABLE = 1 << 23,
         const I2C_4_ENABLE = 1 << 24,
         const CAN_1_ENABLE = 1 << 25,
+        #[cfg(has_can2)]
         const CAN_2_ENABLE = 1 << 26,
+        #[cfg(has_cec)]
         const CEC_ENABLE = 1 << 27,
         const PWR_ENABLE = 1 << 28,
         const DAC_ENABLE = 1 << 29,
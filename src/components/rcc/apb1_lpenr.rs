@@ -0,0 +1,40 @@
+//! RCC APB1 peripheral clock enable in low power mode register (RCC_APB1LPENR)
+
+bitflags! {
+    pub flags Register: u32 {
+        const TIM_2_LP_ENABLE = 1 << 0,
+        const TIM_3_LP_ENABLE = 1 << 1,
+        const TIM_4_LP_ENABLE = 1 << 2,
+        const TIM_5_LP_ENABLE = 1 << 3,
+        const TIM_6_LP_ENABLE = 1 << 4,
+        const TIM_7_LP_ENABLE = 1 << 5,
+        const TIM_12_LP_ENABLE = 1 << 6,
+        const TIM_13_LP_ENABLE = 1 << 7,
+        const TIM_14_LP_ENABLE = 1 << 8,
+        const LPTIM_1_LP_ENABLE = 1 << 9,
+        const WWDG_LP_ENABLE = 1 << 11,
+        #[cfg(has_can3)]
+        const CAN_3_LP_ENABLE = 1 << 13,
+        const SPI_2_LP_ENABLE = 1 << 14,
+        const SPI_3_LP_ENABLE = 1 << 15,
+        #[cfg(has_spdifrx)]
+        const SPDIFRX_LP_ENABLE = 1 << 16,
+        const USART_2_LP_ENABLE = 1 << 17,
+        const USART_3_LP_ENABLE = 1 << 18,
+        const UART_4_LP_ENABLE = 1 << 19,
+        const UART_5_LP_ENABLE = 1 << 20,
+        const I2C_1_LP_ENABLE = 1 << 21,
+        const I2C_2_LP_ENABLE = 1 << 22,
+        const I2C_3_LP_ENABLE = 1 << 23,
+        const I2C_4_LP_ENABLE = 1 << 24,
+        const CAN_1_LP_ENABLE = 1 << 25,
+        #[cfg(has_can2)]
+        const CAN_2_LP_ENABLE = 1 << 26,
+        #[cfg(has_cec)]
+        const CEC_LP_ENABLE = 1 << 27,
+        const PWR_LP_ENABLE = 1 << 28,
+        const DAC_LP_ENABLE = 1 << 29,
+        const UART_7_LP_ENABLE = 1 << 30,
+        const UART_8_LP_ENABLE = 1 << 31,
+    }
+}
@@ -0,0 +1,40 @@
+//! RCC APB1 peripheral reset register (RCC_APB1RSTR)
+
+bitflags! {
+    pub flags Register: u32 {
+        const TIM_2_RESET = 1 << 0,
+        const TIM_3_RESET = 1 << 1,
+        const TIM_4_RESET = 1 << 2,
+        const TIM_5_RESET = 1 << 3,
+        const TIM_6_RESET = 1 << 4,
+        const TIM_7_RESET = 1 << 5,
+        const TIM_12_RESET = 1 << 6,
+        const TIM_13_RESET = 1 << 7,
+        const TIM_14_RESET = 1 << 8,
+        const LPTIM_1_RESET = 1 << 9,
+        const WWDG_RESET = 1 << 11,
+        #[cfg(has_can3)]
+        const CAN_3_RESET = 1 << 13,
+        const SPI_2_RESET = 1 << 14,
+        const SPI_3_RESET = 1 << 15,
+        #[cfg(has_spdifrx)]
+        const SPDIFRX_RESET = 1 << 16,
+        const USART_2_RESET = 1 << 17,
+        const USART_3_RESET = 1 << 18,
+        const UART_4_RESET = 1 << 19,
+        const UART_5_RESET = 1 << 20,
+        const I2C_1_RESET = 1 << 21,
+        const I2C_2_RESET = 1 << 22,
+        const I2C_3_RESET = 1 << 23,
+        const I2C_4_RESET = 1 << 24,
+        const CAN_1_RESET = 1 << 25,
+        #[cfg(has_can2)]
+        const CAN_2_RESET = 1 << 26,
+        #[cfg(has_cec)]
+        const CEC_RESET = 1 << 27,
+        const PWR_RESET = 1 << 28,
+        const DAC_RESET = 1 << 29,
+        const UART_7_RESET = 1 << 30,
+        const UART_8_RESET = 1 << 31,
+    }
+}
@@ -6,6 +6,8 @@ bitflags! {
         const TIM_8_ENABLE = 1 << 1,
         const USART_1_ENABLE = 1 << 4,
         const USART_6_ENABLE = 1 << 5,
+        #[cfg(has_sdmmc2)]
+        const SDMMC_2_ENABLE = 1 << 7,
         const ADC_1_ENABLE = 1 << 8,
         const ADC_2_ENABLE = 1 << 9,
         const ADC_3_ENABLE = 1 << 10,
@@ -20,6 +22,13 @@ bitflags! {
         const SPI_6_ENABLE = 1 << 21,
         const SAI_1_ENABLE = 1 << 22,
         const SAI_2_ENABLE = 1 << 23,
+        #[cfg(has_ltdc)]
         const LTDC_ENABLE = 1 << 26,
+        #[cfg(has_dsi)]
+        const DSI_ENABLE = 1 << 27,
+        #[cfg(has_dfsdm1)]
+        const DFSDM_1_ENABLE = 1 << 29,
+        #[cfg(has_mdio)]
+        const MDIO_ENABLE = 1 << 30,
     }
 }
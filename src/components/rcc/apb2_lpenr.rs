@@ -0,0 +1,34 @@
+//! RCC APB2 peripheral clock enable in low power mode register (RCC_APB2LPENR)
+
+bitflags! {
+    pub flags Register: u32 {
+        const TIM_1_LP_ENABLE = 1 << 0,
+        const TIM_8_LP_ENABLE = 1 << 1,
+        const USART_1_LP_ENABLE = 1 << 4,
+        const USART_6_LP_ENABLE = 1 << 5,
+        #[cfg(has_sdmmc2)]
+        const SDMMC_2_LP_ENABLE = 1 << 7,
+        const ADC_1_LP_ENABLE = 1 << 8,
+        const ADC_2_LP_ENABLE = 1 << 9,
+        const ADC_3_LP_ENABLE = 1 << 10,
+        const SDMMC_1_LP_ENABLE = 1 << 11,
+        const SPI_1_LP_ENABLE = 1 << 12,
+        const SPI_4_LP_ENABLE = 1 << 13,
+        const SYSCFG_LP_ENABLE = 1 << 14,
+        const TIM_9_LP_ENABLE = 1 << 16,
+        const TIM_10_LP_ENABLE = 1 << 17,
+        const TIM_11_LP_ENABLE = 1 << 18,
+        const SPI_5_LP_ENABLE = 1 << 20,
+        const SPI_6_LP_ENABLE = 1 << 21,
+        const SAI_1_LP_ENABLE = 1 << 22,
+        const SAI_2_LP_ENABLE = 1 << 23,
+        #[cfg(has_ltdc)]
+        const LTDC_LP_ENABLE = 1 << 26,
+        #[cfg(has_dsi)]
+        const DSI_LP_ENABLE = 1 << 27,
+        #[cfg(has_dfsdm1)]
+        const DFSDM_1_LP_ENABLE = 1 << 29,
+        #[cfg(has_mdio)]
+        const MDIO_LP_ENABLE = 1 << 30,
+    }
+}
@@ -0,0 +1,33 @@
+//! RCC APB2 peripheral reset register (RCC_APB2RSTR)
+
+bitflags! {
+    pub flags Register: u32 {
+        const TIM_1_RESET = 1 << 0,
+        const TIM_8_RESET = 1 << 1,
+        const USART_1_RESET = 1 << 4,
+        const USART_6_RESET = 1 << 5,
+        #[cfg(has_sdmmc2)]
+        const SDMMC_2_RESET = 1 << 7,
+        /// Resets all three ADCs
+        const ADC_RESET = 1 << 8,
+        const SDMMC_1_RESET = 1 << 11,
+        const SPI_1_RESET = 1 << 12,
+        const SPI_4_RESET = 1 << 13,
+        const SYSCFG_RESET = 1 << 14,
+        const TIM_9_RESET = 1 << 16,
+        const TIM_10_RESET = 1 << 17,
+        const TIM_11_RESET = 1 << 18,
+        const SPI_5_RESET = 1 << 20,
+        const SPI_6_RESET = 1 << 21,
+        const SAI_1_RESET = 1 << 22,
+        const SAI_2_RESET = 1 << 23,
+        #[cfg(has_ltdc)]
+        const LTDC_RESET = 1 << 26,
+        #[cfg(has_dsi)]
+        const DSI_RESET = 1 << 27,
+        #[cfg(has_dfsdm1)]
+        const DFSDM_1_RESET = 1 << 29,
+        #[cfg(has_mdio)]
+        const MDIO_RESET = 1 << 30,
+    }
+}
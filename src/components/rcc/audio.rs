@@ -0,0 +1,303 @@
+//! Audio clock for the SAIs
+//!
+//! The SAI kernel clock comes from the Q output of PLLI2S or PLLSAI, divided by PLLI2SDIVQ or
+//! PLLSAIDIVQ. `AudioClock` targets a kernel clock that equals the master clock (MCLK), i.e.
+//! the sample rate times the MCLK ratio.
+
+use core::cmp;
+use super::{RccBank, Peripheral, cr};
+use super::clocks::{Error, VCO_OUTPUT_MIN, VCO_OUTPUT_MAX, OSCILLATOR_TIMEOUT, wait};
+use super::dckcfgr1::SaiClockSource;
+use super::dckcfgr2::Clk48Source;
+
+/// Base sample rate of an audio clock family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleRateFamily {
+    /// 44.1 kHz (CD audio) and its multiples
+    Hz44100,
+    /// 48 kHz (professional audio) and its multiples
+    Hz48000,
+}
+
+impl SampleRateFamily {
+    pub fn base_rate(&self) -> u32 {
+        match *self {
+            SampleRateFamily::Hz44100 => 44_100,
+            SampleRateFamily::Hz48000 => 48_000,
+        }
+    }
+}
+
+/// PLL dividers for the SAI kernel clock, as returned by `solve_audio_pll`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioPllConfig {
+    pub n: u32,
+    pub q: u32,
+    pub divq: u32,
+    /// Deviation from the requested clock in parts per million
+    pub ppm: i32,
+}
+
+/// Searches PLL dividers for the SAI kernel clock that is closest to `target`.
+///
+/// `input` is the PLL input frequency and `m` the PLLM value of the main PLL. If `n` is given,
+/// the VCO frequency is kept and only Q and DIVQ are searched, so that the other outputs of a
+/// running PLL keep their frequencies.
+pub fn solve_audio_pll(input: u32, m: u32, target: u32, n: Option<u32>) -> Option<AudioPllConfig> {
+    if input == 0 || m == 0 || target == 0 {
+        return None;
+    }
+    // only the N values that keep the VCO output in range
+    let (n_min, n_max) = match n {
+        Some(n) => (n, n + 1),
+        None => {
+            let n_min = (VCO_OUTPUT_MIN as u64 * m as u64 + input as u64 - 1) / input as u64;
+            let n_max = VCO_OUTPUT_MAX as u64 * m as u64 / input as u64;
+            (cmp::max(n_min, 50) as u32, cmp::min(n_max, 432) as u32 + 1)
+        }
+    };
+    let mut best: Option<(u64, AudioPllConfig)> = None;
+
+    for n in n_min..n_max {
+        let vco = input as u64 * n as u64 / m as u64;
+        if vco < VCO_OUTPUT_MIN as u64 || vco > VCO_OUTPUT_MAX as u64 {
+            continue;
+        }
+        for q in 2..16 {
+            for divq in 1..33 {
+                // compare input * n / (m * q * divq) with target without rounding
+                let actual = input as u64 * n as u64;
+                let wanted = target as u64 * m as u64 * q as u64 * divq as u64;
+                let deviation = if actual > wanted { actual - wanted } else { wanted - actual };
+                let ppm = deviation * 1_000_000 / wanted;
+                if best.map(|(best_ppm, _)| ppm < best_ppm).unwrap_or(true) {
+                    let sign = if actual < wanted { -1 } else { 1 };
+                    best = Some((ppm,
+                                 AudioPllConfig {
+                                     n: n,
+                                     q: q,
+                                     divq: divq,
+                                     ppm: sign * ppm as i32,
+                                 }));
+                }
+            }
+        }
+    }
+
+    best.map(|(_, config)| config)
+}
+
+/// Configuration of the SAI kernel clock for a sample rate
+///
+/// ```ignore
+/// let setup = AudioClock::new(SampleRateFamily::Hz48000, 1, 256).sai2().apply(rcc, Some(HSE))?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioClock {
+    family: SampleRateFamily,
+    multiplier: u32,
+    mclk_ratio: u32,
+    sai1: bool,
+    sai2: bool,
+}
+
+/// Result of `AudioClock::apply`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioClockSetup {
+    /// PLL that clocks the selected SAIs
+    pub source: SaiClockSource,
+    pub config: AudioPllConfig,
+    /// Achieved master clock in Hz
+    pub mclk: u32,
+}
+
+impl AudioClock {
+    /// Creates a configuration for `multiplier` times the base rate of `family`, with a master
+    /// clock of `mclk_ratio` times the sample rate.
+    pub fn new(family: SampleRateFamily, multiplier: u32, mclk_ratio: u32) -> AudioClock {
+        AudioClock {
+            family: family,
+            multiplier: multiplier,
+            mclk_ratio: mclk_ratio,
+            sai1: false,
+            sai2: false,
+        }
+    }
+
+    /// Selects the audio clock as kernel clock of SAI1.
+    pub fn sai1(mut self) -> AudioClock {
+        self.sai1 = true;
+        self
+    }
+
+    /// Selects the audio clock as kernel clock of SAI2.
+    pub fn sai2(mut self) -> AudioClock {
+        self.sai2 = true;
+        self
+    }
+
+    /// Returns the sample rate, or `None` if it doesn't fit in a `u32`.
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.family.base_rate().checked_mul(self.multiplier)
+    }
+
+    /// Returns the requested master clock frequency, or `None` if it doesn't fit in a `u32`.
+    pub fn mclk(&self) -> Option<u32> {
+        self.sample_rate().and_then(|rate| rate.checked_mul(self.mclk_ratio))
+    }
+
+    /// Programs the PLL that reaches the master clock best and selects it for the SAIs.
+    ///
+    /// The selected PLL is stopped while it is reprogrammed, so a running PLL isn't considered
+    /// if a SAI that isn't selected runs from it. PLLSAI isn't considered either while it clocks
+    /// the 48 MHz domain or the LTDC is enabled. A running PLL is only considered with its
+    /// current N, which keeps its other outputs: PLLSAI always, PLLI2S while SPDIF-RX or one of
+    /// the SPIs that can run in I2S mode is enabled. On equal deviation, PLLI2S is preferred.
+    /// Fails with `NoAudioPllConfiguration` if the master clock overflows.
+    pub fn apply(self, rcc: &mut RccBank, hse: Option<u32>) -> Result<AudioClockSetup, Error> {
+        let input = rcc.pll_input(hse)?;
+        let m = rcc.pll_cfgr.read().pllm();
+        let target = self.mclk().ok_or(Error::NoAudioPllConfiguration)?;
+        let plli2s_on = rcc.cr.read().contains(cr::PLLI2S_ON);
+        let pllsai_on = rcc.cr.read().contains(cr::PLLSAI_ON);
+
+        let plli2s = if plli2s_on && self.other_sai_uses(rcc, SaiClockSource::PllI2S) {
+            None
+        } else if plli2s_on && plli2s_in_use(rcc) {
+            solve_audio_pll(input, m, target, Some(rcc.plli2scfgr.read().plli2sn()))
+        } else {
+            solve_audio_pll(input, m, target, None)
+        };
+        let pllsai = if pllsai_in_use(rcc) ||
+                        (pllsai_on && self.other_sai_uses(rcc, SaiClockSource::PllSai)) {
+            None
+        } else if pllsai_on {
+            solve_audio_pll(input, m, target, Some(rcc.pllsaicfgr.read().pllsain()))
+        } else {
+            solve_audio_pll(input, m, target, None)
+        };
+
+        let (source, config) = match (plli2s, pllsai) {
+            (Some(i2s), Some(sai)) if sai.ppm.abs() < i2s.ppm.abs() => {
+                (SaiClockSource::PllSai, sai)
+            }
+            (Some(i2s), _) => (SaiClockSource::PllI2S, i2s),
+            (None, Some(sai)) => (SaiClockSource::PllSai, sai),
+            (None, None) => return Err(Error::NoAudioPllConfiguration),
+        };
+
+        match source {
+            SaiClockSource::PllSai => {
+                rcc.cr.update(|r| r.remove(cr::PLLSAI_ON));
+                if !wait(OSCILLATOR_TIMEOUT, || !rcc.cr.read().contains(cr::PLLSAI_RDY)) {
+                    return Err(Error::PllSaiTimeout);
+                }
+                rcc.pllsaicfgr.update(|r| {
+                    r.set_pllsain(config.n);
+                    r.set_pllsaiq(config.q);
+                });
+                rcc.dckcfgr1.update(|r| r.set_pllsai_divq(config.divq));
+                rcc.cr.update(|r| r.insert(cr::PLLSAI_ON));
+                if !wait(OSCILLATOR_TIMEOUT, || rcc.cr.read().contains(cr::PLLSAI_RDY)) {
+                    return Err(Error::PllSaiTimeout);
+                }
+            }
+            _ => {
+                rcc.cr.update(|r| r.remove(cr::PLLI2S_ON));
+                if !wait(OSCILLATOR_TIMEOUT, || !rcc.cr.read().contains(cr::PLLI2S_RDY)) {
+                    return Err(Error::PllI2sTimeout);
+                }
+                rcc.plli2scfgr.update(|r| {
+                    r.set_plli2sn(config.n);
+                    r.set_plli2sq(config.q);
+                });
+                rcc.dckcfgr1.update(|r| r.set_plli2s_divq(config.divq));
+                rcc.cr.update(|r| r.insert(cr::PLLI2S_ON));
+                if !wait(OSCILLATOR_TIMEOUT, || rcc.cr.read().contains(cr::PLLI2S_RDY)) {
+                    return Err(Error::PllI2sTimeout);
+                }
+            }
+        }
+        rcc.dckcfgr1.update(|r| {
+            if self.sai1 {
+                r.set_sai1_clock_source(source);
+            }
+            if self.sai2 {
+                r.set_sai2_clock_source(source);
+            }
+        });
+
+        let mclk = (input as u64 * config.n as u64 /
+                    (m as u64 * config.q as u64 * config.divq as u64)) as u32;
+        Ok(AudioClockSetup {
+            source: source,
+            config: config,
+            mclk: mclk,
+        })
+    }
+
+    /// Returns whether a SAI that isn't selected runs from the given PLL.
+    fn other_sai_uses(&self, rcc: &RccBank, source: SaiClockSource) -> bool {
+        let dckcfgr1 = rcc.dckcfgr1.read();
+        (!self.sai1 && dckcfgr1.sai1_clock_source() == Some(source)) ||
+        (!self.sai2 && dckcfgr1.sai2_clock_source() == Some(source))
+    }
+}
+
+/// Returns whether PLLI2S may clock an I2S or SPDIF-RX.
+///
+/// The mode of the SPIs isn't checked, so any enabled SPI that supports I2S counts.
+fn plli2s_in_use(rcc: &RccBank) -> bool {
+    #[cfg(has_spdifrx)]
+    let spdifrx = rcc.is_enabled(Peripheral::SpdifRx);
+    #[cfg(not(has_spdifrx))]
+    let spdifrx = false;
+    spdifrx ||
+    [Peripheral::Spi1, Peripheral::Spi2, Peripheral::Spi3].iter().any(|&spi| rcc.is_enabled(spi))
+}
+
+/// Returns whether PLLSAI clocks the 48 MHz domain or the LCD.
+fn pllsai_in_use(rcc: &RccBank) -> bool {
+    #[cfg(has_ltdc)]
+    let lcd = rcc.is_enabled(Peripheral::Ltdc);
+    #[cfg(not(has_ltdc))]
+    let lcd = false;
+    lcd || rcc.dckcfgr2.read().clk48_source() == Clk48Source::PllSai
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mclk_48khz() {
+        // 48 kHz with 256 times MCLK from the 25 MHz crystal of the discovery board
+        let config = solve_audio_pll(25_000_000, 25, 12_288_000, None).unwrap();
+        assert!(config.ppm.abs() <= 200);
+        assert!(config.n >= 100 && config.n <= 432);
+    }
+
+    #[test]
+    fn fixed_n() {
+        let config = solve_audio_pll(25_000_000, 25, 11_289_600, Some(384)).unwrap();
+        assert_eq!(config.n, 384);
+    }
+
+    #[test]
+    fn vco_out_of_range() {
+        assert_eq!(solve_audio_pll(25_000_000, 25, 12_288_000, Some(50)), None);
+    }
+
+    #[test]
+    fn mclk_overflow() {
+        assert_eq!(AudioClock::new(SampleRateFamily::Hz48000, 1, 256).mclk(), Some(12_288_000));
+        assert_eq!(AudioClock::new(SampleRateFamily::Hz48000, 1 << 16, 256).mclk(), None);
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(solve_audio_pll(25_000_000, 0, 12_288_000, None), None);
+        assert_eq!(solve_audio_pll(25_000_000, 25, 0, None), None);
+        assert_eq!(solve_audio_pll(0, 25, 12_288_000, None), None);
+    }
+}
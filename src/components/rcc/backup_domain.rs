@@ -0,0 +1,184 @@
+//! Backup domain with the LSE oscillator and the RTC clock selection
+//!
+//! The backup domain keeps its state over resets and, with a backup battery, over power loss.
+//! It is write protected after reset. `RccBank::backup_domain` enables write access through
+//! `pwr::cr1::DBP` and returns a `BackupDomain`, which protects it again when it is dropped, unless
+//! write access was already enabled before.
+//!
+//! ```ignore
+//! let mut backup = rcc.backup_domain(pwr)?;
+//! backup.start_lse(LseDrive::MediumHigh, false)?;
+//! backup.set_rtc_clock(RtcClockSource::Lse)?;
+//! ```
+
+use super::{RccBank, Peripheral, csr};
+use super::bdcr::{LseDrive, RtcClockSource};
+use super::clocks::Error;
+use components::pwr::{self, PwrBank};
+
+/// Default number of ready flag polls before an oscillator start or stop fails
+///
+/// Covers the worst case LSE startup time of 2 s at 216 MHz.
+pub const DEFAULT_TIMEOUT: u32 = 0x1000_0000;
+
+/// Write access to the backup domain
+pub struct BackupDomain<'a> {
+    rcc: &'a mut RccBank,
+    pwr: &'a mut PwrBank,
+    timeout: u32,
+    /// Whether DBP was set before, so that dropping keeps write access enabled
+    was_writable: bool,
+}
+
+impl RccBank {
+    /// Enables write access to the backup domain.
+    ///
+    /// The PWR clock is enabled, because DBP is in the PWR registers. Fails with
+    /// `BackupDomainTimeout` if DBP doesn't read back as set.
+    pub fn backup_domain<'a>(&'a mut self,
+                             pwr: &'a mut PwrBank)
+                             -> Result<BackupDomain<'a>, Error> {
+        self.enable(Peripheral::Pwr);
+        let was_writable = pwr.cr1.read().contains(pwr::cr1::DBP);
+        pwr.cr1.update(|r| r.insert(pwr::cr1::DBP));
+        if !wait(DEFAULT_TIMEOUT, || pwr.cr1.read().contains(pwr::cr1::DBP)) {
+            return Err(Error::BackupDomainTimeout);
+        }
+
+        Ok(BackupDomain {
+            rcc: self,
+            pwr: pwr,
+            timeout: DEFAULT_TIMEOUT,
+            was_writable: was_writable,
+        })
+    }
+}
+
+impl<'a> BackupDomain<'a> {
+    /// Sets the number of ready flag polls before an oscillator start or stop fails.
+    ///
+    /// The duration of a poll depends on the system clock.
+    pub fn timeout(mut self, polls: u32) -> BackupDomain<'a> {
+        self.timeout = polls;
+        self
+    }
+
+    /// Starts the LSE oscillator with the given drive and waits until it is ready.
+    ///
+    /// With `bypass`, LSE is driven by an external clock signal instead of a crystal. A running
+    /// LSE is only restarted if its configuration differs, since it may clock the RTC. If LSE
+    /// doesn't become ready in time, it is stopped again.
+    pub fn start_lse(&mut self, drive: LseDrive, bypass: bool) -> Result<(), Error> {
+        let bdcr = self.rcc.bdcr.read();
+        if bdcr.lse_ready() && bdcr.lse_drive() == drive && bdcr.lse_bypass() == bypass {
+            return Ok(());
+        }
+
+        // bypass and drive can only be changed while LSE is off
+        self.stop_lse()?;
+        self.rcc.bdcr.update(|r| {
+            r.set_lse_bypass(bypass);
+            r.set_lse_drive(drive);
+        });
+        self.rcc.bdcr.update(|r| r.set_lse_on(true));
+
+        let rcc = &self.rcc;
+        if wait(self.timeout, || rcc.bdcr.read().lse_ready()) {
+            Ok(())
+        } else {
+            // the start already failed, which is the error to report
+            let _ = self.stop_lse();
+            Err(Error::LseTimeout)
+        }
+    }
+
+    /// Stops the LSE oscillator and waits until its ready flag is cleared.
+    pub fn stop_lse(&mut self) -> Result<(), Error> {
+        self.rcc.bdcr.update(|r| r.set_lse_on(false));
+        let rcc = &self.rcc;
+        if wait(self.timeout, || !rcc.bdcr.read().lse_ready()) {
+            Ok(())
+        } else {
+            Err(Error::LseTimeout)
+        }
+    }
+
+    /// Starts the LSI oscillator and waits until it is ready.
+    ///
+    /// LSI isn't part of the backup domain, but is the alternative RTC clock without a crystal.
+    pub fn start_lsi(&mut self) -> Result<(), Error> {
+        self.rcc.csr.update(|r| r.insert(csr::LSI_ON));
+
+        let rcc = &self.rcc;
+        if wait(self.timeout, || rcc.csr.read().contains(csr::LSI_RDY)) {
+            Ok(())
+        } else {
+            self.stop_lsi();
+            Err(Error::LsiTimeout)
+        }
+    }
+
+    /// Stops the LSI oscillator.
+    ///
+    /// The independent watchdog forces LSI on while it runs.
+    pub fn stop_lsi(&mut self) {
+        self.rcc.csr.update(|r| r.remove(csr::LSI_ON));
+    }
+
+    /// Selects the RTC clock source and enables the RTC clock.
+    ///
+    /// The source can only be selected once after a backup domain reset, so selecting a
+    /// different source fails with `RtcClockAlreadySelected`. For `Hse`, the RTC prescaler in
+    /// `cfgr` has to be set before.
+    pub fn set_rtc_clock(&mut self, source: RtcClockSource) -> Result<(), Error> {
+        let current = self.rcc.bdcr.read().rtc_clock_source();
+        if current != RtcClockSource::NoClock && current != source {
+            return Err(Error::RtcClockAlreadySelected(current));
+        }
+        if source == RtcClockSource::Hse {
+            let prescaler = self.rcc.cfgr.read().rtc_prescaler();
+            if prescaler < 2 {
+                return Err(Error::InvalidRtcPrescaler(prescaler));
+            }
+        }
+
+        self.rcc.bdcr.update(|r| {
+            r.set_rtc_clock_source(source);
+            r.set_rtc_enabled(source != RtcClockSource::NoClock);
+        });
+        Ok(())
+    }
+
+    /// Returns the selected RTC clock source.
+    pub fn rtc_clock(&self) -> RtcClockSource {
+        self.rcc.bdcr.read().rtc_clock_source()
+    }
+
+    /// Resets the backup domain.
+    ///
+    /// This stops LSE and the RTC, clears the RTC clock selection and the RTC backup registers.
+    pub fn reset(&mut self) {
+        self.rcc.bdcr.update(|r| r.set_backup_domain_reset(true));
+        self.rcc.bdcr.update(|r| r.set_backup_domain_reset(false));
+    }
+}
+
+impl<'a> Drop for BackupDomain<'a> {
+    fn drop(&mut self) {
+        if !self.was_writable {
+            self.pwr.cr1.update(|r| r.remove(pwr::cr1::DBP));
+        }
+    }
+}
+
+/// Polls `ready` at most `polls` times and returns whether it became true.
+fn wait<F>(polls: u32, ready: F) -> bool
+    where F: Fn() -> bool
+{
+    for _ in 0..polls {
+        if ready() {
+            return true;
+        }
+    }
+    false
+}
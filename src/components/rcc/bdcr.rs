@@ -0,0 +1,97 @@
+//! RCC backup domain control register (RCC_BDCR)
+//!
+//! The register is part of the backup domain and can only be written after write access was
+//! enabled through `pwr::cr1::DBP`.
+
+use bit_field::BitField;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Register(BitField<u32>);
+
+impl Register {
+    pub fn lse_on(&self) -> bool {
+        self.0.get_bit(0)
+    }
+
+    pub fn set_lse_on(&mut self, value: bool) {
+        self.0.set_bit(0, value);
+    }
+
+    pub fn lse_ready(&self) -> bool {
+        self.0.get_bit(1)
+    }
+
+    /// LSE is bypassed with an external clock signal
+    pub fn lse_bypass(&self) -> bool {
+        self.0.get_bit(2)
+    }
+
+    /// Can only be changed while LSE is off.
+    pub fn set_lse_bypass(&mut self, value: bool) {
+        self.0.set_bit(2, value);
+    }
+
+    pub fn lse_drive(&self) -> LseDrive {
+        match self.0.get_range(3..5) {
+            0b00 => LseDrive::Low,
+            0b01 => LseDrive::MediumHigh,
+            0b10 => LseDrive::MediumLow,
+            0b11 => LseDrive::High,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn set_lse_drive(&mut self, value: LseDrive) {
+        self.0.set_range(3..5, value as u32);
+    }
+
+    pub fn rtc_clock_source(&self) -> RtcClockSource {
+        match self.0.get_range(8..10) {
+            0b00 => RtcClockSource::NoClock,
+            0b01 => RtcClockSource::Lse,
+            0b10 => RtcClockSource::Lsi,
+            0b11 => RtcClockSource::Hse,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Can only be changed once after a backup domain reset.
+    pub fn set_rtc_clock_source(&mut self, value: RtcClockSource) {
+        self.0.set_range(8..10, value as u32);
+    }
+
+    pub fn rtc_enabled(&self) -> bool {
+        self.0.get_bit(15)
+    }
+
+    pub fn set_rtc_enabled(&mut self, value: bool) {
+        self.0.set_bit(15, value);
+    }
+
+    /// Backup domain software reset
+    pub fn set_backup_domain_reset(&mut self, value: bool) {
+        self.0.set_bit(16, value);
+    }
+}
+
+/// Drive capability of the LSE oscillator
+///
+/// A higher drive starts more crystals reliably, but consumes more power.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum LseDrive {
+    Low = 0b00,
+    MediumHigh = 0b01,
+    MediumLow = 0b10,
+    High = 0b11,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum RtcClockSource {
+    NoClock = 0b00,
+    Lse = 0b01,
+    Lsi = 0b10,
+    /// HSE divided by the RTC prescaler in `cfgr`
+    Hse = 0b11,
+}
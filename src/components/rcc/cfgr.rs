@@ -7,7 +7,7 @@ use bit_field::BitField;
 pub struct Register(BitField<u32>);
 
 impl Register {
-    pub fn system_clock(&mut self) -> Option<SystemClock> {
+    pub fn system_clock(&self) -> Option<SystemClock> {
         match self.0.get_range(2..4) {
             0b00 => Some(SystemClock::HSI),
             0b01 => Some(SystemClock::HSE),
@@ -21,20 +21,43 @@ impl Register {
         self.0.set_range(0..2, value as u32);
     }
 
+    pub fn ahb_prescaler(&self) -> AhbClockDivisionFactor {
+        AhbClockDivisionFactor::from_bits(self.0.get_range(4..8))
+    }
+
     pub fn set_ahb_prescaler(&mut self, value: AhbClockDivisionFactor) {
         self.0.set_range(4..8, value as u32);
     }
 
+    pub fn apb_low_speed_prescaler(&self) -> ApbClockDivisionFactor {
+        ApbClockDivisionFactor::from_bits(self.0.get_range(10..13))
+    }
+
     pub fn set_apb_low_speed_prescaler(&mut self, value: ApbClockDivisionFactor) {
         self.0.set_range(10..13, value as u32);
     }
 
+    pub fn apb_high_speed_prescaler(&self) -> ApbClockDivisionFactor {
+        ApbClockDivisionFactor::from_bits(self.0.get_range(13..16))
+    }
+
     pub fn set_apb_high_speed_prescaler(&mut self, value: ApbClockDivisionFactor) {
         self.0.set_range(13..16, value as u32);
     }
+
+    /// Divisor of the HSE clock for the RTC, where 0 and 1 mean no clock
+    pub fn rtc_prescaler(&self) -> u32 {
+        self.0.get_range(16..21)
+    }
+
+    /// Sets the HSE divisor for the RTC, from 2 to 31, so that the RTC clock is 1 MHz at most.
+    pub fn set_rtc_prescaler(&mut self, value: u32) {
+        assert!(value < 32, "invalid RTC prescaler");
+        self.0.set_range(16..21, value);
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum SystemClock {
     HSI = 0b00,
@@ -56,6 +79,40 @@ pub enum AhbClockDivisionFactor {
     Divide512 = 0b1111,
 }
 
+impl AhbClockDivisionFactor {
+    /// Returns the number the clock is divided by.
+    pub fn divisor(&self) -> u32 {
+        use self::AhbClockDivisionFactor::*;
+        match *self {
+            NoDivide => 1,
+            Divide2 => 2,
+            Divide4 => 4,
+            Divide8 => 8,
+            Divide16 => 16,
+            Divide64 => 64,
+            Divide128 => 128,
+            Divide256 => 256,
+            Divide512 => 512,
+        }
+    }
+
+    fn from_bits(bits: u32) -> AhbClockDivisionFactor {
+        use self::AhbClockDivisionFactor::*;
+        match bits {
+            0b1000 => Divide2,
+            0b1001 => Divide4,
+            0b1010 => Divide8,
+            0b1011 => Divide16,
+            0b1100 => Divide64,
+            0b1101 => Divide128,
+            0b1110 => Divide256,
+            0b1111 => Divide512,
+            // all values with the highest bit cleared mean no division
+            _ => NoDivide,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 #[repr(u32)]
 pub enum ApbClockDivisionFactor {
@@ -65,3 +122,29 @@ pub enum ApbClockDivisionFactor {
     Divide8 = 0b110,
     Divide16 = 0b111,
 }
+
+impl ApbClockDivisionFactor {
+    /// Returns the number the clock is divided by.
+    pub fn divisor(&self) -> u32 {
+        use self::ApbClockDivisionFactor::*;
+        match *self {
+            NoDivide => 1,
+            Divide2 => 2,
+            Divide4 => 4,
+            Divide8 => 8,
+            Divide16 => 16,
+        }
+    }
+
+    fn from_bits(bits: u32) -> ApbClockDivisionFactor {
+        use self::ApbClockDivisionFactor::*;
+        match bits {
+            0b100 => Divide2,
+            0b101 => Divide4,
+            0b110 => Divide8,
+            0b111 => Divide16,
+            // all values with the highest bit cleared mean no division
+            _ => NoDivide,
+        }
+    }
+}
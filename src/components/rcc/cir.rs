@@ -0,0 +1,34 @@
+//! RCC clock interrupt register (RCC_CIR)
+
+bitflags! {
+    pub flags Register: u32 {
+        const LSI_RDY_F = 1 << 0,
+        const LSE_RDY_F = 1 << 1,
+        const HSI_RDY_F = 1 << 2,
+        const HSE_RDY_F = 1 << 3,
+        const PLL_RDY_F = 1 << 4,
+        const PLLI2S_RDY_F = 1 << 5,
+        const PLLSAI_RDY_F = 1 << 6,
+        /// Clock security system interrupt flag, set when an HSE failure is detected
+        const CSS_F = 1 << 7,
+
+        const LSI_RDY_IE = 1 << 8,
+        const LSE_RDY_IE = 1 << 9,
+        const HSI_RDY_IE = 1 << 10,
+        const HSE_RDY_IE = 1 << 11,
+        const PLL_RDY_IE = 1 << 12,
+        const PLLI2S_RDY_IE = 1 << 13,
+        const PLLSAI_RDY_IE = 1 << 14,
+
+        // the clear bits are write-only and always read as zero
+        const LSI_RDY_C = 1 << 16,
+        const LSE_RDY_C = 1 << 17,
+        const HSI_RDY_C = 1 << 18,
+        const HSE_RDY_C = 1 << 19,
+        const PLL_RDY_C = 1 << 20,
+        const PLLI2S_RDY_C = 1 << 21,
+        const PLLSAI_RDY_C = 1 << 22,
+        /// Clears `CSS_F`
+        const CSS_C = 1 << 23,
+    }
+}
@@ -0,0 +1,754 @@
+//! Clock tree configuration
+//!
+//! `ClockConfig` derives the PLL dividers, the bus prescalers, the voltage scale and the flash
+//! wait states from a clock source and a target system clock. The PLL dividers are computed by
+//! `solve_pll`, which doesn't access any register.
+
+use super::{RccBank, cr, cfgr};
+use super::cfgr::{AhbClockDivisionFactor, ApbClockDivisionFactor};
+use super::Peripheral;
+use super::dckcfgr2::{self, Clk48Source};
+use super::kernel::{self, KernelClock};
+use super::bdcr::RtcClockSource;
+use components::pwr::{self, PwrBank};
+use components::flash::FlashBank;
+
+/// Frequency of the internal RC oscillator
+pub const HSI_FREQ: u32 = 16_000_000;
+/// Frequency of the external low speed crystal that kernel clock frequencies assume
+pub const LSE_FREQ: u32 = 32_768;
+/// Typical frequency of the internal low speed RC oscillator
+pub const LSI_FREQ: u32 = 32_000;
+/// Maximal system clock frequency, which requires over-drive mode
+pub const SYSCLK_MAX: u32 = 216_000_000;
+
+const HSE_MIN: u32 = 4_000_000;
+const HSE_MAX: u32 = 26_000_000;
+const VCO_INPUT_MIN: u32 = 950_000;
+const VCO_INPUT_MAX: u32 = 2_100_000;
+/// Lowest VCO output frequency of all three PLLs
+pub const VCO_OUTPUT_MIN: u32 = 100_000_000;
+/// Highest VCO output frequency of all three PLLs
+pub const VCO_OUTPUT_MAX: u32 = 432_000_000;
+/// Frequency of the 48 MHz domain for USB OTG FS, SDMMC and RNG
+pub const PLL48_FREQ: u32 = 48_000_000;
+
+/// Highest HCLK frequency without over-drive mode
+const OVERDRIVE_THRESHOLD: u32 = 180_000_000;
+/// Number of ready flag polls before an oscillator, the PLL or a clock switch times out
+///
+/// Runs from HSI, so this is well above the 100 ms that a slow crystal may take to start.
+pub const OSCILLATOR_TIMEOUT: u32 = 0x0010_0000;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The HSE frequency is outside of the 4 to 26 MHz range.
+    InvalidHseFrequency(u32),
+    /// The requested system clock is zero or above 216 MHz.
+    InvalidSysclk(u32),
+    /// No PLL configuration reaches the system clock (and an exact 48 MHz clock if required).
+    NoPllConfiguration,
+    /// The PLLs run from HSE, but the HSE frequency wasn't given.
+    UnknownHseFrequency,
+    /// No PLLSAI configuration reaches the requested clock.
+    NoPllSaiConfiguration,
+    /// PLLSAI didn't lock or stop within `OSCILLATOR_TIMEOUT`.
+    PllSaiTimeout,
+    /// PLLI2S didn't lock or stop within `OSCILLATOR_TIMEOUT`.
+    PllI2sTimeout,
+    /// Neither PLLI2S nor PLLSAI can produce the requested audio clock.
+    NoAudioPllConfiguration,
+    /// The peripheral can't run from the kernel clock.
+    InvalidKernelClock(Peripheral, KernelClock),
+    /// HSI didn't become ready within `OSCILLATOR_TIMEOUT`.
+    HsiTimeout,
+    /// HSE didn't start or stop within `OSCILLATOR_TIMEOUT`, e.g. because the crystal is missing.
+    HseTimeout,
+    /// The main PLL didn't lock or stop within `OSCILLATOR_TIMEOUT`.
+    PllTimeout,
+    /// Over-drive mode didn't become ready within `OSCILLATOR_TIMEOUT`.
+    OverDriveTimeout,
+    /// The system clock switch didn't complete within `OSCILLATOR_TIMEOUT`.
+    ClockSwitchTimeout,
+    /// PLLI2S or PLLSAI is running, so the input and PLLM shared with the main PLL can't change.
+    PllI2sOrPllSaiRunning,
+    /// LSE didn't start or stop within the timeout.
+    LseTimeout,
+    /// LSI didn't become ready within the timeout.
+    LsiTimeout,
+    /// Write access to the backup domain (DBP) didn't become enabled within the timeout.
+    BackupDomainTimeout,
+    /// The RTC clock source can only be changed after a backup domain reset.
+    RtcClockAlreadySelected(RtcClockSource),
+    /// The HSE divisor for the RTC is below 2, which stops the RTC clock.
+    InvalidRtcPrescaler(u32),
+}
+
+/// Input clock of the PLL and the system clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockSource {
+    /// Internal 16 MHz RC oscillator
+    Hsi,
+    /// External crystal or, if `bypass` is set, external clock signal with the given frequency
+    Hse { freq: u32, bypass: bool },
+}
+
+impl ClockSource {
+    pub fn freq(&self) -> u32 {
+        match *self {
+            ClockSource::Hsi => HSI_FREQ,
+            ClockSource::Hse { freq, .. } => freq,
+        }
+    }
+}
+
+/// Dividers of the main PLL
+///
+/// SYSCLK is `input / m * n / p` and PLL48CLK is `input / m * n / q`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PllConfig {
+    pub m: u32,
+    pub n: u32,
+    pub p: u32,
+    pub q: u32,
+}
+
+impl PllConfig {
+    /// Returns the frequency of the VCO output for the given input frequency.
+    pub fn vco_freq(&self, input: u32) -> u32 {
+        (input as u64 * self.n as u64 / self.m as u64) as u32
+    }
+
+    /// Returns the system clock frequency for the given input frequency.
+    pub fn sysclk(&self, input: u32) -> u32 {
+        (input as u64 * self.n as u64 / (self.m as u64 * self.p as u64)) as u32
+    }
+
+    /// Returns the frequency of the 48 MHz domain for the given input frequency.
+    pub fn pll48clk(&self, input: u32) -> u32 {
+        (input as u64 * self.n as u64 / (self.m as u64 * self.q as u64)) as u32
+    }
+}
+
+/// Searches PLL dividers for the highest system clock that doesn't exceed `sysclk`.
+///
+/// The VCO input is kept between 0.95 and 2.1 MHz and the VCO output between 100 and 432 MHz.
+/// If `require_48mhz` is set, only configurations with an exact 48 MHz PLL48CLK are considered;
+/// otherwise PLLQ is chosen so that PLL48CLK doesn't exceed 48 MHz. Among equally good
+/// configurations, the one with the highest VCO input frequency (the least jitter) wins.
+pub fn solve_pll(input: u32, sysclk: u32, require_48mhz: bool) -> Option<PllConfig> {
+    let mut best: Option<(u32, PllConfig)> = None;
+
+    for m in 2..64 {
+        let vco_input = input / m;
+        if vco_input < VCO_INPUT_MIN || vco_input > VCO_INPUT_MAX {
+            continue;
+        }
+        for n in 50..433 {
+            let vco = input as u64 * n as u64;
+            if vco < VCO_OUTPUT_MIN as u64 * m as u64 || vco > VCO_OUTPUT_MAX as u64 * m as u64 {
+                continue;
+            }
+
+            let q = if require_48mhz {
+                // the VCO output must be an exact multiple of 48 MHz
+                if vco % (PLL48_FREQ as u64 * m as u64) != 0 {
+                    continue;
+                }
+                let q = vco / (PLL48_FREQ as u64 * m as u64);
+                if q < 2 || q > 15 {
+                    continue;
+                }
+                q as u32
+            } else {
+                let q = (vco + PLL48_FREQ as u64 * m as u64 - 1) / (PLL48_FREQ as u64 * m as u64);
+                if q > 15 {
+                    continue;
+                }
+                if q < 2 { 2 } else { q as u32 }
+            };
+
+            for &p in [2, 4, 6, 8].iter() {
+                let config = PllConfig {
+                    m: m,
+                    n: n,
+                    p: p,
+                    q: q,
+                };
+                let freq = config.sysclk(input);
+                if freq > sysclk {
+                    continue;
+                }
+                if best.map(|(best_freq, _)| freq > best_freq).unwrap_or(true) {
+                    best = Some((freq, config));
+                }
+            }
+        }
+    }
+
+    best.map(|(_, config)| config)
+}
+
+/// Builder for the clock tree configuration
+///
+/// ```ignore
+/// let clocks = ClockConfig::hse(25_000_000).sysclk(216_000_000).require_48mhz()
+///     .freeze(rcc, pwr, flash)?;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ClockConfig {
+    source: ClockSource,
+    sysclk: u32,
+    require_48mhz: bool,
+}
+
+impl ClockConfig {
+    /// Starts a configuration that runs from the internal 16 MHz oscillator.
+    pub fn hsi() -> ClockConfig {
+        ClockConfig {
+            source: ClockSource::Hsi,
+            sysclk: HSI_FREQ,
+            require_48mhz: false,
+        }
+    }
+
+    /// Starts a configuration that runs from an external crystal with the given frequency.
+    pub fn hse(freq: u32) -> ClockConfig {
+        ClockConfig {
+            source: ClockSource::Hse {
+                freq: freq,
+                bypass: false,
+            },
+            sysclk: freq,
+            require_48mhz: false,
+        }
+    }
+
+    /// Starts a configuration that runs from an external clock signal (`HSE_BYP`).
+    pub fn hse_bypass(freq: u32) -> ClockConfig {
+        ClockConfig {
+            source: ClockSource::Hse {
+                freq: freq,
+                bypass: true,
+            },
+            sysclk: freq,
+            require_48mhz: false,
+        }
+    }
+
+    /// Replaces the clock source and keeps the other settings.
+    pub fn source(mut self, source: ClockSource) -> ClockConfig {
+        self.source = source;
+        self
+    }
+
+    /// Sets the target system clock frequency.
+    ///
+    /// The PLL is used if the target differs from the source frequency. The actual frequency is
+    /// the highest one the PLL can reach without exceeding the target.
+    pub fn sysclk(mut self, freq: u32) -> ClockConfig {
+        self.sysclk = freq;
+        self
+    }
+
+    /// Requires an exact 48 MHz clock for USB OTG FS, SDMMC and RNG.
+    pub fn require_48mhz(mut self) -> ClockConfig {
+        self.require_48mhz = true;
+        self
+    }
+
+    /// Returns the PLL dividers that the configuration would use, or `None` if the system clock
+    /// comes directly from the source.
+    pub fn pll(&self) -> Result<Option<PllConfig>, Error> {
+        if let ClockSource::Hse { freq, .. } = self.source {
+            if freq < HSE_MIN || freq > HSE_MAX {
+                return Err(Error::InvalidHseFrequency(freq));
+            }
+        }
+        if self.sysclk == 0 || self.sysclk > SYSCLK_MAX {
+            return Err(Error::InvalidSysclk(self.sysclk));
+        }
+
+        let input = self.source.freq();
+        if self.sysclk == input && !self.require_48mhz {
+            return Ok(None);
+        }
+        match solve_pll(input, self.sysclk, self.require_48mhz) {
+            Some(pll) => Ok(Some(pll)),
+            None => Err(Error::NoPllConfiguration),
+        }
+    }
+
+    /// Applies the configuration and returns the resulting clock frequencies.
+    ///
+    /// The system clock is switched to HSI while the PLL is reprogrammed. The voltage scale is
+    /// set while the PLL is off, over-drive mode is enabled after the PLL locked, and the flash
+    /// wait states are increased before the system clock is switched to the new source. The wait
+    /// states assume a supply voltage between 2.7 and 3.6 V.
+    ///
+    /// If HSE or the PLL doesn't become ready, it is stopped again and the system keeps running
+    /// from HSI. PLLI2S and PLLSAI share the input and PLLM of the main PLL, which can only be
+    /// changed while all PLLs are off, so this fails with `PllI2sOrPllSaiRunning` if one of them
+    /// runs.
+    pub fn freeze(self,
+                  rcc: &mut RccBank,
+                  pwr: &mut PwrBank,
+                  flash: &mut FlashBank)
+                  -> Result<Clocks, Error> {
+        let pll = self.pll()?;
+        let input = self.source.freq();
+        let sysclk = match pll {
+            Some(pll) => pll.sysclk(input),
+            None => input,
+        };
+
+        if rcc.cr.read().intersects(cr::PLLI2S_ON | cr::PLLSAI_ON) {
+            return Err(Error::PllI2sOrPllSaiRunning);
+        }
+
+        // run from HSI while the PLL and the source are reconfigured
+        rcc.cr.update(|r| r.insert(cr::HSI_ON));
+        if !wait(OSCILLATOR_TIMEOUT, || rcc.cr.read().contains(cr::HSI_RDY)) {
+            return Err(Error::HsiTimeout);
+        }
+        switch_system_clock(rcc, cfgr::SystemClock::HSI)?;
+        rcc.cr.update(|r| r.remove(cr::PLL_ON));
+        if !wait(OSCILLATOR_TIMEOUT, || !rcc.cr.read().contains(cr::PLL_RDY)) {
+            return Err(Error::PllTimeout);
+        }
+
+        if let ClockSource::Hse { bypass, .. } = self.source {
+            rcc.cr.update(|r| r.remove(cr::HSE_ON));
+            if !wait(OSCILLATOR_TIMEOUT, || !rcc.cr.read().contains(cr::HSE_RDY)) {
+                return Err(Error::HseTimeout);
+            }
+            rcc.cr.update(|r| if bypass {
+                r.insert(cr::HSE_BYP)
+            } else {
+                r.remove(cr::HSE_BYP)
+            });
+            rcc.cr.update(|r| r.insert(cr::HSE_ON));
+            if !wait(OSCILLATOR_TIMEOUT, || rcc.cr.read().contains(cr::HSE_RDY)) {
+                rcc.cr.update(|r| r.remove(cr::HSE_ON));
+                return Err(Error::HseTimeout);
+            }
+        }
+
+        // voltage scaling can only be changed while the PLL is off
+        rcc.enable(Peripheral::Pwr);
+        pwr.cr1.update(|r| {
+            r.remove(pwr::cr1::VOS_0 | pwr::cr1::VOS_1 | pwr::cr1::ODEN | pwr::cr1::ODSWEN);
+            r.insert(voltage_scale(sysclk));
+        });
+
+        if let Some(pll) = pll {
+            rcc.pll_cfgr.update(|r| {
+                r.set_pllsrc(self.source != ClockSource::Hsi);
+                r.set_pllm(pll.m);
+                r.set_plln(pll.n);
+                r.set_pllp(pll.p);
+                r.set_pllq(pll.q);
+            });
+            rcc.cr.update(|r| r.insert(cr::PLL_ON));
+            if !wait(OSCILLATOR_TIMEOUT, || rcc.cr.read().contains(cr::PLL_RDY)) {
+                rcc.cr.update(|r| r.remove(cr::PLL_ON));
+                return Err(Error::PllTimeout);
+            }
+        }
+
+        if sysclk > OVERDRIVE_THRESHOLD {
+            pwr.cr1.update(|r| r.insert(pwr::cr1::ODEN));
+            if !wait(OSCILLATOR_TIMEOUT, || pwr.csr1.read().contains(pwr::csr1::OD_RDY)) {
+                return Err(Error::OverDriveTimeout);
+            }
+            pwr.cr1.update(|r| r.insert(pwr::cr1::ODSWEN));
+            if !wait(OSCILLATOR_TIMEOUT, || pwr.csr1.read().contains(pwr::csr1::ODSW_RDY)) {
+                return Err(Error::OverDriveTimeout);
+            }
+        }
+
+        // the flash is accessed at HSI speed at the moment, so any latency is safe
+        flash.acr.update(|r| r.set_latency(flash_latency(sysclk)));
+
+        // the APB limits are higher in over-drive mode
+        let (apb1_max, apb2_max) = if sysclk > OVERDRIVE_THRESHOLD {
+            (54_000_000, 108_000_000)
+        } else {
+            (45_000_000, 90_000_000)
+        };
+        let apb1 = apb_prescaler(sysclk, apb1_max);
+        let apb2 = apb_prescaler(sysclk, apb2_max);
+        rcc.cfgr.update(|r| {
+            r.set_ahb_prescaler(AhbClockDivisionFactor::NoDivide);
+            r.set_apb_low_speed_prescaler(apb1);
+            r.set_apb_high_speed_prescaler(apb2);
+        });
+
+        match (pll, self.source) {
+            (Some(_), _) => switch_system_clock(rcc, cfgr::SystemClock::PLL)?,
+            (None, ClockSource::Hse { .. }) => switch_system_clock(rcc, cfgr::SystemClock::HSE)?,
+            (None, ClockSource::Hsi) => {}
+        }
+
+        let hse = match self.source {
+            ClockSource::Hse { freq, .. } => Some(freq),
+            ClockSource::Hsi => None,
+        };
+        Ok(rcc.clocks(hse))
+    }
+}
+
+impl RccBank {
+    /// Returns the input frequency of the PLLs, which is HSE or HSI depending on PLLSRC.
+    pub fn pll_input(&self, hse: Option<u32>) -> Result<u32, Error> {
+        if !self.pll_cfgr.read().pllsrc() {
+            return Ok(HSI_FREQ);
+        }
+        match hse {
+            Some(hse) => Ok(hse),
+            None => Err(Error::UnknownHseFrequency),
+        }
+    }
+
+    /// Computes the current clock frequencies from the RCC registers.
+    ///
+    /// `hse` is the frequency of the external crystal or clock signal. If it is `None`, all
+    /// clocks that are derived from HSE are reported as zero.
+    pub fn clocks(&self, hse: Option<u32>) -> Clocks {
+        let cr = self.cr.read();
+        let pll_cfgr = self.pll_cfgr.read();
+        let cfgr = self.cfgr.read();
+
+        // all three PLLs share the input clock and the PLLM divider
+        let pll_input = if pll_cfgr.pllsrc() {
+            hse.unwrap_or(0)
+        } else {
+            HSI_FREQ
+        };
+        let pllm = pll_cfgr.pllm();
+        let pll = if cr.contains(cr::PLL_RDY) {
+            let vco = vco_freq(pll_input, pllm, pll_cfgr.plln());
+            let pllq = pll_cfgr.pllq();
+            Some((vco / pll_cfgr.pllp(), if pllq == 0 { 0 } else { vco / pllq }))
+        } else {
+            None
+        };
+
+        let sysclk = match cfgr.system_clock() {
+            Some(cfgr::SystemClock::HSI) => HSI_FREQ,
+            Some(cfgr::SystemClock::HSE) => hse.unwrap_or(0),
+            Some(cfgr::SystemClock::PLL) => pll.map(|pll| pll.0).unwrap_or(0),
+            None => 0,
+        };
+        let hclk = sysclk / cfgr.ahb_prescaler().divisor();
+        let apb1 = cfgr.apb_low_speed_prescaler().divisor();
+        let apb2 = cfgr.apb_high_speed_prescaler().divisor();
+        let timpre = self.dckcfgr1.read().timpre();
+
+        let pllsai = if cr.contains(cr::PLLSAI_RDY) {
+            let pllsaicfgr = self.pllsaicfgr.read();
+            Some(PllOutputs::new(vco_freq(pll_input, pllm, pllsaicfgr.pllsain()),
+                                 pllsaicfgr.pllsaip(),
+                                 pllsaicfgr.pllsaiq(),
+                                 pllsaicfgr.pllsair()))
+        } else {
+            None
+        };
+        let plli2s = if cr.contains(cr::PLLI2S_RDY) {
+            let plli2scfgr = self.plli2scfgr.read();
+            Some(PllOutputs::new(vco_freq(pll_input, pllm, plli2scfgr.plli2sn()),
+                                 plli2scfgr.plli2sp(),
+                                 plli2scfgr.plli2sq(),
+                                 plli2scfgr.plli2sr()))
+        } else {
+            None
+        };
+
+        let dckcfgr2 = self.dckcfgr2.read();
+        let pll48clk = match dckcfgr2.clk48_source() {
+            Clk48Source::Pll => pll.map(|pll| pll.1),
+            Clk48Source::PllSai => pllsai.map(|pllsai| pllsai.p),
+        };
+
+        Clocks {
+            sysclk: sysclk,
+            hclk: hclk,
+            pclk1: hclk / apb1,
+            pclk2: hclk / apb2,
+            timclk1: timer_clock(hclk, apb1, timpre),
+            timclk2: timer_clock(hclk, apb2, timpre),
+            pll48clk: pll48clk,
+            pllsai: pllsai,
+            plli2s: plli2s,
+            dckcfgr2: dckcfgr2,
+        }
+    }
+}
+
+/// Output frequencies of the PLLSAI or PLLI2S, in Hz
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PllOutputs {
+    pub p: u32,
+    pub q: u32,
+    pub r: u32,
+}
+
+impl PllOutputs {
+    /// Divides the VCO output frequency by the P, Q and R dividers.
+    pub fn new(vco: u32, p: u32, q: u32, r: u32) -> PllOutputs {
+        // invalid divider values of zero are reported as stopped outputs
+        let divide = |divider: u32| if divider == 0 { 0 } else { vco / divider };
+        PllOutputs {
+            p: divide(p),
+            q: divide(q),
+            r: divide(r),
+        }
+    }
+}
+
+/// Frozen clock frequencies, in Hz
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clocks {
+    sysclk: u32,
+    hclk: u32,
+    pclk1: u32,
+    pclk2: u32,
+    timclk1: u32,
+    timclk2: u32,
+    pll48clk: Option<u32>,
+    pllsai: Option<PllOutputs>,
+    plli2s: Option<PllOutputs>,
+    dckcfgr2: dckcfgr2::Register,
+}
+
+impl Clocks {
+    /// System clock
+    pub fn sysclk(&self) -> u32 {
+        self.sysclk
+    }
+
+    /// AHB clock, which also clocks the core
+    pub fn hclk(&self) -> u32 {
+        self.hclk
+    }
+
+    /// Low speed APB clock (APB1)
+    pub fn pclk1(&self) -> u32 {
+        self.pclk1
+    }
+
+    /// High speed APB clock (APB2)
+    pub fn pclk2(&self) -> u32 {
+        self.pclk2
+    }
+
+    /// Clock of the timers on APB1
+    pub fn timclk1(&self) -> u32 {
+        self.timclk1
+    }
+
+    /// Clock of the timers on APB2
+    pub fn timclk2(&self) -> u32 {
+        self.timclk2
+    }
+
+    /// 48 MHz domain clock from the main PLL or the PLLSAI, depending on CK48MSEL, or `None` if
+    /// the selected PLL is off
+    pub fn pll48clk(&self) -> Option<u32> {
+        self.pll48clk
+    }
+
+    /// Outputs of the PLLSAI, or `None` if it is off
+    pub fn pllsai(&self) -> Option<PllOutputs> {
+        self.pllsai
+    }
+
+    /// Outputs of the PLLI2S, or `None` if it is off
+    pub fn plli2s(&self) -> Option<PllOutputs> {
+        self.plli2s
+    }
+
+    /// Returns the kernel clock frequency of a peripheral with a kernel clock mux.
+    ///
+    /// Returns `None` for other peripherals and if the 48 MHz clock is selected but its PLL
+    /// output is off. HSI, LSE and LSI aren't checked: their nominal frequency is returned even if
+    /// the oscillator isn't running. LSE is assumed to run at 32.768 kHz and LSI at its typical
+    /// 32 kHz.
+    pub fn kernel_clock(&self, peripheral: Peripheral) -> Option<u32> {
+        let pclk = match peripheral {
+            Peripheral::Usart1 | Peripheral::Usart6 => self.pclk2,
+            _ => self.pclk1,
+        };
+        match kernel::kernel_clock(&self.dckcfgr2, peripheral) {
+            Some(KernelClock::Apb) => Some(pclk),
+            Some(KernelClock::Sysclk) => Some(self.sysclk),
+            Some(KernelClock::Hsi) => Some(HSI_FREQ),
+            Some(KernelClock::HsiDiv488) => Some(HSI_FREQ / 488),
+            Some(KernelClock::Lse) => Some(LSE_FREQ),
+            Some(KernelClock::Lsi) => Some(LSI_FREQ),
+            Some(KernelClock::Pll) |
+            Some(KernelClock::PllSai) |
+            Some(KernelClock::Clk48) => self.pll48clk,
+            None => None,
+        }
+    }
+}
+
+fn switch_system_clock(rcc: &mut RccBank, clock: cfgr::SystemClock) -> Result<(), Error> {
+    rcc.cfgr.update(|r| r.set_system_clock(clock));
+    if wait(OSCILLATOR_TIMEOUT, || rcc.cfgr.read().system_clock() == Some(clock)) {
+        Ok(())
+    } else {
+        Err(Error::ClockSwitchTimeout)
+    }
+}
+
+/// Polls `ready` at most `polls` times and returns whether it became true.
+pub(super) fn wait<F>(polls: u32, ready: F) -> bool
+    where F: Fn() -> bool
+{
+    for _ in 0..polls {
+        if ready() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Returns the lowest voltage scale that supports the given HCLK frequency.
+fn voltage_scale(hclk: u32) -> pwr::cr1::Register {
+    if hclk <= 144_000_000 {
+        pwr::cr1::VOS_0
+    } else if hclk <= 168_000_000 {
+        pwr::cr1::VOS_1
+    } else {
+        pwr::cr1::VOS_0 | pwr::cr1::VOS_1
+    }
+}
+
+/// Returns the flash wait states for the given HCLK frequency at 2.7 to 3.6 V.
+fn flash_latency(hclk: u32) -> u32 {
+    (hclk - 1) / 30_000_000
+}
+
+/// Returns the smallest APB prescaler that keeps the bus clock at or below `max`.
+fn apb_prescaler(hclk: u32, max: u32) -> ApbClockDivisionFactor {
+    let prescalers = [(ApbClockDivisionFactor::NoDivide, 1),
+                      (ApbClockDivisionFactor::Divide2, 2),
+                      (ApbClockDivisionFactor::Divide4, 4),
+                      (ApbClockDivisionFactor::Divide8, 8)];
+    for &(prescaler, divider) in prescalers.iter() {
+        if hclk / divider <= max {
+            return prescaler;
+        }
+    }
+    ApbClockDivisionFactor::Divide16
+}
+
+/// Returns the VCO output frequency of a PLL, or zero if the PLLM value is invalid.
+fn vco_freq(input: u32, m: u32, n: u32) -> u32 {
+    if m == 0 {
+        return 0;
+    }
+    (input as u64 * n as u64 / m as u64) as u32
+}
+
+/// Returns the clock of the timers on an APB bus with the given prescaler.
+///
+/// If the bus is divided, the timers run at twice the bus clock, or with `TIMPRE` at four times
+/// the bus clock but at most HCLK.
+fn timer_clock(hclk: u32, apb_divisor: u32, timpre: bool) -> u32 {
+    match (apb_divisor, timpre) {
+        (1, _) => hclk,
+        (_, false) => hclk / apb_divisor * 2,
+        (2, true) | (4, true) => hclk,
+        (_, true) => hclk / apb_divisor * 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hse_25mhz_to_216mhz_with_48mhz() {
+        let config = solve_pll(25_000_000, 216_000_000, true).unwrap();
+        assert_eq!(config,
+                   PllConfig {
+                       m: 25,
+                       n: 432,
+                       p: 2,
+                       q: 9,
+                   });
+        assert_eq!(config.sysclk(25_000_000), 216_000_000);
+        assert_eq!(config.pll48clk(25_000_000), PLL48_FREQ);
+    }
+
+    #[test]
+    fn hse_8mhz_to_216mhz() {
+        let config = solve_pll(8_000_000, 216_000_000, false).unwrap();
+        assert_eq!(config.sysclk(8_000_000), 216_000_000);
+        assert!(config.pll48clk(8_000_000) <= PLL48_FREQ);
+        // the highest VCO input frequency wins
+        assert_eq!(config.m, 4);
+
+        let config = solve_pll(8_000_000, 216_000_000, true).unwrap();
+        assert_eq!(config.sysclk(8_000_000), 216_000_000);
+        assert_eq!(config.pll48clk(8_000_000), PLL48_FREQ);
+    }
+
+    #[test]
+    fn hsi_to_180mhz() {
+        let config = solve_pll(HSI_FREQ, 180_000_000, false).unwrap();
+        assert_eq!(config.sysclk(HSI_FREQ), 180_000_000);
+        assert!(config.pll48clk(HSI_FREQ) <= PLL48_FREQ);
+        let vco = config.vco_freq(HSI_FREQ);
+        assert!(vco >= VCO_OUTPUT_MIN && vco <= VCO_OUTPUT_MAX);
+    }
+
+    #[test]
+    fn sysclk_below_vco_range() {
+        // the lowest system clock is 100 MHz / 8
+        assert_eq!(solve_pll(25_000_000, 12_000_000, false), None);
+        assert!(solve_pll(25_000_000, 12_500_000, false).is_some());
+    }
+
+    #[test]
+    fn input_below_vco_input_range() {
+        // even with PLLM = 2, the VCO input stays below 0.95 MHz
+        assert_eq!(solve_pll(1_800_000, 216_000_000, false), None);
+    }
+
+    #[test]
+    fn exact_48mhz_unreachable() {
+        // an odd input without the factor 5 can't be multiplied to a multiple of 48 MHz
+        assert_eq!(solve_pll(24_999_999, 216_000_000, true), None);
+        assert!(solve_pll(24_999_999, 216_000_000, false).is_some());
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert!(match ClockConfig::hse(3_000_000).sysclk(216_000_000).pll() {
+            Err(Error::InvalidHseFrequency(3_000_000)) => true,
+            _ => false,
+        });
+        assert!(match ClockConfig::hsi().sysclk(SYSCLK_MAX + 1).pll() {
+            Err(Error::InvalidSysclk(_)) => true,
+            _ => false,
+        });
+        assert!(match ClockConfig::hsi().pll() {
+            Ok(None) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn flash_latency_boundaries() {
+        assert_eq!(flash_latency(30_000_000), 0);
+        assert_eq!(flash_latency(30_000_001), 1);
+        assert_eq!(flash_latency(31_000_000), 1);
+        assert_eq!(flash_latency(210_000_000), 6);
+        assert_eq!(flash_latency(211_000_000), 7);
+        assert_eq!(flash_latency(SYSCLK_MAX), 7);
+    }
+}
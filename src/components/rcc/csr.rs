@@ -0,0 +1,17 @@
+//! RCC clock control & status register (RCC_CSR)
+
+bitflags! {
+    pub flags Register: u32 {
+        const LSI_ON = 1 << 0,
+        const LSI_RDY = 1 << 1,
+        /// Clears all reset flags
+        const RMVF = 1 << 24,
+        const BOR_RST_F = 1 << 25,
+        const PIN_RST_F = 1 << 26,
+        const POR_RST_F = 1 << 27,
+        const SFT_RST_F = 1 << 28,
+        const IWDG_RST_F = 1 << 29,
+        const WWDG_RST_F = 1 << 30,
+        const LPWR_RST_F = 1 << 31,
+    }
+}
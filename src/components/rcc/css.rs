@@ -0,0 +1,120 @@
+//! Clock security system (CSS)
+//!
+//! If the CSS detects a failure of the HSE oscillator, the hardware switches the system clock to
+//! HSI, stops HSE and, if HSE was its input, the main PLL, and raises a non-maskable interrupt.
+//! `clock_security_nmi` handles this interrupt and has to be registered as NMI handler:
+//!
+//! ```ignore
+//! board!(stm32f7_discovery, {
+//!     nmi: Some(embedded::components::rcc::clock_security_nmi)
+//! });
+//! ```
+//!
+//! The handler only acknowledges the failure, records it and calls the callback with the HSI
+//! clocks. It doesn't relock the PLL: an NMI can't be masked, so its read-modify-writes of CR and
+//! CFGR could undo those of the interrupted code. Recovery therefore needs the application to
+//! poll `RccBank::relock_after_hse_failure` in thread context, e.g. from the main loop:
+//!
+//! ```ignore
+//! if let Some(clocks) = rcc.relock_after_hse_failure(pwr, flash)? {
+//!     // reconfigure the peripherals that depend on the clock frequencies
+//! }
+//! ```
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use super::{RccBank, ClockConfig, ClockSource, Clocks, cr, cfgr, cir};
+use super::clocks::{Error, OSCILLATOR_TIMEOUT, wait};
+use components::pwr::PwrBank;
+use components::flash::FlashBank;
+use peripherals::Peripherals;
+
+/// Called by the NMI handler after an HSE failure
+static mut CALLBACK: Option<fn(Clocks)> = None;
+/// Configuration that `relock_after_hse_failure` applies with HSI as source
+static mut RELOCK: Option<ClockConfig> = None;
+/// Set by the NMI handler, cleared by `relock_after_hse_failure`
+static HSE_FAILED: AtomicBool = AtomicBool::new(false);
+
+impl RccBank {
+    /// Enables the clock security system, which watches the HSE oscillator while it runs.
+    ///
+    /// After an HSE failure, the NMI handler calls `callback` with the HSI clocks. The callback
+    /// runs in NMI context, so it can't be masked and should only record the new clocks. If
+    /// `relock` is given, `relock_after_hse_failure` applies it with HSI as PLL input.
+    pub fn enable_clock_security(&mut self, relock: Option<ClockConfig>, callback: fn(Clocks)) {
+        unsafe {
+            RELOCK = relock;
+            CALLBACK = Some(callback);
+        }
+        self.cr.update(|r| r.insert(cr::CSS_ON));
+    }
+
+    /// Disables the clock security system.
+    pub fn disable_clock_security(&mut self) {
+        self.cr.update(|r| r.remove(cr::CSS_ON));
+        unsafe {
+            RELOCK = None;
+            CALLBACK = None;
+        }
+    }
+
+    /// Returns whether the NMI handler recorded an HSE failure that wasn't handled yet.
+    pub fn hse_failed(&self) -> bool {
+        HSE_FAILED.load(Ordering::SeqCst)
+    }
+
+    /// Handles a recorded HSE failure in thread context.
+    ///
+    /// Clears HSE_ON and CSS_ON, selects HSI in the SW field, applies the `relock` configuration
+    /// of `enable_clock_security` with HSI as PLL input and returns the new clocks, or the HSI
+    /// clocks without a `relock` configuration. Returns `Ok(None)` if no failure was recorded.
+    /// Like `ClockConfig::freeze`, relocking fails while PLLI2S or PLLSAI runs.
+    pub fn relock_after_hse_failure(&mut self,
+                                    pwr: &mut PwrBank,
+                                    flash: &mut FlashBank)
+                                    -> Result<Option<Clocks>, Error> {
+        if !HSE_FAILED.swap(false, Ordering::SeqCst) {
+            return Ok(None);
+        }
+
+        // the hardware already runs from HSI, so make CR and the SW field agree
+        self.cr.update(|r| r.remove(cr::HSE_ON | cr::CSS_ON));
+        self.cfgr.update(|r| r.set_system_clock(cfgr::SystemClock::HSI));
+        if !wait(OSCILLATOR_TIMEOUT,
+                 || self.cfgr.read().system_clock() == Some(cfgr::SystemClock::HSI)) {
+            return Err(Error::ClockSwitchTimeout);
+        }
+
+        let relock = unsafe { RELOCK };
+        match relock {
+            Some(config) => config.source(ClockSource::Hsi).freeze(self, pwr, flash).map(Some),
+            None => Ok(Some(self.clocks(None))),
+        }
+    }
+}
+
+/// NMI handler for HSE failures.
+///
+/// Clears the CSS flag, confirms that the system clock fell back to HSI, records the failure for
+/// `RccBank::relock_after_hse_failure` and calls the callback. CR and CFGR are only read, the
+/// changes they need are left to thread context. Does nothing if the NMI wasn't caused by the
+/// clock security system.
+pub extern "C" fn clock_security_nmi() {
+    let hw = unsafe { Peripherals::steal() };
+    let rcc = hw.rcc;
+    if !rcc.cir.read().contains(cir::CSS_F) {
+        return;
+    }
+    // a plain store, since the clear bits are write-1-to-clear and a read-modify-write could
+    // race with thread code; this leaves the unused ready interrupts disabled
+    rcc.cir.write(cir::CSS_C);
+
+    // the hardware switches to HSI on its own, so the status is only read
+    wait(OSCILLATOR_TIMEOUT,
+         || rcc.cfgr.read().system_clock() == Some(cfgr::SystemClock::HSI));
+    HSE_FAILED.store(true, Ordering::SeqCst);
+
+    if let Some(callback) = unsafe { CALLBACK } {
+        callback(rcc.clocks(None));
+    }
+}
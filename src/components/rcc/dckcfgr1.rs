@@ -6,43 +6,97 @@ use bit_field::BitField;
 pub struct Register(BitField<u32>);
 
 impl Register {
-    /**
-    division factor for LCD_CLK
+    /// Division factor for LCD_CLK
+    ///
+    /// LCD_CLK frequency = f(PLLSAI_R) / PLLSAIDIVR, where PLLSAIDIVR is 2, 4, 8 or 16.
+    pub fn pllsai_divr(&self) -> u32 {
+        2 << self.0.get_range(16..18)
+    }
 
-    These bits are set and cleared by software to control the frequency of LCD_CLK.
-    They should be written only if PLLSAI is disabled.
-    LCD_CLK frequency = f(PLLSAI_R) / PLLSAIDIVR with 2 ≤ PLLSAIDIVR ≤ 16
-    **/
+    /// Sets the division factor for LCD_CLK.
+    ///
+    /// Only the values 2, 4, 8 and 16 are valid; other values panic. Should be written only if
+    /// PLLSAI is disabled.
     pub fn set_pllsai_divr(&mut self, number: u32) {
         let bits = match number {
             2 => 0b00,
             4 => 0b01,
             8 => 0b10,
             16 => 0b11,
-            _ => panic!("invalid value"),
+            _ => panic!("invalid pllsai_divr value (must be 2, 4, 8 or 16)"),
         };
         self.0.set_range(16..18, bits);
     }
 
+    /// Raw PLLSAIDIVQ field, which is the division factor minus one
     pub fn pllsai_divq(&self) -> u32 {
         self.0.get_range(8..13)
     }
 
+    /// Division factor for the SAI clock from PLLSAI (1 to 32)
+    pub fn pllsai_divq_factor(&self) -> u32 {
+        self.0.get_range(8..13) + 1
+    }
+
+    pub fn set_pllsai_divq(&mut self, number: u32) {
+        assert!(number >= 1 && number <= 32);
+        self.0.set_range(8..13, number - 1);
+    }
+
+    /// Kernel clock of SAI1, or `None` for the reserved value
+    pub fn sai1_clock_source(&self) -> Option<SaiClockSource> {
+        SaiClockSource::from_bits(self.0.get_range(20..22))
+    }
+
+    pub fn set_sai1_clock_source(&mut self, clock_source: SaiClockSource) {
+        self.0.set_range(20..22, clock_source as u32);
+    }
+
+    /// Kernel clock of SAI2, or `None` for the reserved value
+    pub fn sai2_clock_source(&self) -> Option<SaiClockSource> {
+        SaiClockSource::from_bits(self.0.get_range(22..24))
+    }
+
     pub fn set_sai2_clock_source(&mut self, clock_source: SaiClockSource) {
         self.0.set_range(22..24, clock_source as u32);
     }
 
+    /// Division factor for the SAI clock from PLLI2S (1 to 32)
+    pub fn plli2s_divq_factor(&self) -> u32 {
+        self.0.get_range(0..5) + 1
+    }
+
     pub fn set_plli2s_divq(&mut self, number: u32) {
+        assert!(number >= 1 && number <= 32);
         self.0.set_range(0..5, number - 1);
     }
 
+    /// Timers clocks prescalers selection
+    ///
+    /// If set, the timer clocks are four times the APB clock (but at most HCLK) instead of twice
+    /// the APB clock when the APB prescaler divides.
+    pub fn timpre(&self) -> bool {
+        self.0.get_bit(24)
+    }
+
     // etc
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum SaiClockSource {
     PllI2S = 0b00,
     PllSai = 0b01,
     PinInput = 0b10,
 }
+
+impl SaiClockSource {
+    fn from_bits(bits: u32) -> Option<SaiClockSource> {
+        match bits {
+            0b00 => Some(SaiClockSource::PllI2S),
+            0b01 => Some(SaiClockSource::PllSai),
+            0b10 => Some(SaiClockSource::PinInput),
+            _ => None,
+        }
+    }
+}
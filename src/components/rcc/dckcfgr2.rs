@@ -0,0 +1,178 @@
+//! RCC dedicated clocks configuration register 2 (RCC_DCKCFGR2)
+
+use bit_field::BitField;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Register(BitField<u32>);
+
+impl Register {
+    /// Kernel clock of USART1, USART2, USART3, UART4, UART5, USART6, UART7 or UART8
+    ///
+    /// `number` is the number of the U(S)ART, from 1 to 8.
+    pub fn uart_clock_source(&self, number: u8) -> UartClockSource {
+        let offset = Register::uart_offset(number);
+        match self.0.get_range(offset..offset + 2) {
+            0b00 => UartClockSource::Apb,
+            0b01 => UartClockSource::Sysclk,
+            0b10 => UartClockSource::Hsi,
+            0b11 => UartClockSource::Lse,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn set_uart_clock_source(&mut self, number: u8, source: UartClockSource) {
+        let offset = Register::uart_offset(number);
+        self.0.set_range(offset..offset + 2, source as u32);
+    }
+
+    /// Kernel clock of I2C1 to I2C4, or `None` for the reserved value
+    ///
+    /// `number` is the number of the I2C, from 1 to 4.
+    pub fn i2c_clock_source(&self, number: u8) -> Option<I2cClockSource> {
+        let offset = Register::i2c_offset(number);
+        match self.0.get_range(offset..offset + 2) {
+            0b00 => Some(I2cClockSource::Apb1),
+            0b01 => Some(I2cClockSource::Sysclk),
+            0b10 => Some(I2cClockSource::Hsi),
+            _ => None,
+        }
+    }
+
+    pub fn set_i2c_clock_source(&mut self, number: u8, source: I2cClockSource) {
+        let offset = Register::i2c_offset(number);
+        self.0.set_range(offset..offset + 2, source as u32);
+    }
+
+    pub fn lptim1_clock_source(&self) -> LptimClockSource {
+        match self.0.get_range(24..26) {
+            0b00 => LptimClockSource::Apb1,
+            0b01 => LptimClockSource::Lsi,
+            0b10 => LptimClockSource::Hsi,
+            0b11 => LptimClockSource::Lse,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn set_lptim1_clock_source(&mut self, source: LptimClockSource) {
+        self.0.set_range(24..26, source as u32);
+    }
+
+    pub fn cec_clock_source(&self) -> CecClockSource {
+        if self.0.get_bit(26) {
+            CecClockSource::HsiDiv488
+        } else {
+            CecClockSource::Lse
+        }
+    }
+
+    pub fn set_cec_clock_source(&mut self, source: CecClockSource) {
+        self.0.set_bit(26, source == CecClockSource::HsiDiv488);
+    }
+
+    /// Source of the 48 MHz domain (CK48MSEL), which clocks USB OTG FS, RNG and SDMMC
+    pub fn clk48_source(&self) -> Clk48Source {
+        if self.0.get_bit(27) {
+            Clk48Source::PllSai
+        } else {
+            Clk48Source::Pll
+        }
+    }
+
+    pub fn set_clk48_source(&mut self, source: Clk48Source) {
+        self.0.set_bit(27, source == Clk48Source::PllSai);
+    }
+
+    pub fn sdmmc1_clock_source(&self) -> SdmmcClockSource {
+        if self.0.get_bit(28) {
+            SdmmcClockSource::Sysclk
+        } else {
+            SdmmcClockSource::Clk48
+        }
+    }
+
+    pub fn set_sdmmc1_clock_source(&mut self, source: SdmmcClockSource) {
+        self.0.set_bit(28, source == SdmmcClockSource::Sysclk);
+    }
+
+    #[cfg(has_sdmmc2)]
+    pub fn sdmmc2_clock_source(&self) -> SdmmcClockSource {
+        if self.0.get_bit(29) {
+            SdmmcClockSource::Sysclk
+        } else {
+            SdmmcClockSource::Clk48
+        }
+    }
+
+    #[cfg(has_sdmmc2)]
+    pub fn set_sdmmc2_clock_source(&mut self, source: SdmmcClockSource) {
+        self.0.set_bit(29, source == SdmmcClockSource::Sysclk);
+    }
+
+    fn uart_offset(number: u8) -> u8 {
+        assert!(number >= 1 && number <= 8, "invalid U(S)ART number");
+        (number - 1) * 2
+    }
+
+    fn i2c_offset(number: u8) -> u8 {
+        assert!(number >= 1 && number <= 4, "invalid I2C number");
+        16 + (number - 1) * 2
+    }
+}
+
+// compared by `Clocks`, which keeps a copy of the register
+impl PartialEq for Register {
+    fn eq(&self, other: &Register) -> bool {
+        self.0.bits() == other.0.bits()
+    }
+}
+
+impl Eq for Register {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum UartClockSource {
+    /// APB2 clock for USART1 and USART6, APB1 clock for the others
+    Apb = 0b00,
+    Sysclk = 0b01,
+    Hsi = 0b10,
+    Lse = 0b11,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum I2cClockSource {
+    Apb1 = 0b00,
+    Sysclk = 0b01,
+    Hsi = 0b10,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum LptimClockSource {
+    Apb1 = 0b00,
+    Lsi = 0b01,
+    Hsi = 0b10,
+    Lse = 0b11,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CecClockSource {
+    Lse,
+    /// HSI divided by 488
+    HsiDiv488,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clk48Source {
+    /// Q output of the main PLL
+    Pll,
+    /// P output of the PLLSAI
+    PllSai,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SdmmcClockSource {
+    /// 48 MHz domain clock
+    Clk48,
+    Sysclk,
+}
@@ -0,0 +1,208 @@
+//! Kernel clock selection of the peripherals with a dedicated clock mux in DCKCFGR2
+//!
+//! The U(S)ARTs, I2Cs, LPTIM1, HDMI-CEC and SDMMCs can run from a kernel clock that is
+//! independent of their bus clock, so that e.g. a UART keeps its baud rate while the system
+//! clock changes.
+
+use super::{RccBank, Peripheral};
+use super::clocks::Error;
+use super::dckcfgr2::{self, UartClockSource, I2cClockSource, LptimClockSource, Clk48Source,
+                      SdmmcClockSource};
+#[cfg(has_cec)]
+use super::dckcfgr2::CecClockSource;
+
+/// Kernel clock of a peripheral
+///
+/// Each peripheral only supports some of the sources, see `RccBank::set_kernel_clock`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KernelClock {
+    /// Clock of the APB bus of the peripheral
+    Apb,
+    Sysclk,
+    Hsi,
+    /// HSI divided by 488 (HDMI-CEC only)
+    HsiDiv488,
+    Lse,
+    Lsi,
+    /// Q output of the main PLL (48 MHz domain)
+    Pll,
+    /// P output of the PLLSAI (48 MHz domain)
+    PllSai,
+    /// 48 MHz domain clock (SDMMC only)
+    Clk48,
+}
+
+/// Returns the number of the U(S)ART, if the peripheral is one.
+fn uart_number(peripheral: Peripheral) -> Option<u8> {
+    use super::Peripheral::*;
+    match peripheral {
+        Usart1 => Some(1),
+        Usart2 => Some(2),
+        Usart3 => Some(3),
+        Uart4 => Some(4),
+        Uart5 => Some(5),
+        Usart6 => Some(6),
+        Uart7 => Some(7),
+        Uart8 => Some(8),
+        _ => None,
+    }
+}
+
+/// Returns the number of the I2C, if the peripheral is one.
+fn i2c_number(peripheral: Peripheral) -> Option<u8> {
+    use super::Peripheral::*;
+    match peripheral {
+        I2c1 => Some(1),
+        I2c2 => Some(2),
+        I2c3 => Some(3),
+        #[cfg(has_i2c4)]
+        I2c4 => Some(4),
+        _ => None,
+    }
+}
+
+/// Decodes the kernel clock of the peripheral from DCKCFGR2.
+///
+/// Returns `None` for peripherals without a kernel clock mux and for reserved values.
+pub fn kernel_clock(dckcfgr2: &dckcfgr2::Register, peripheral: Peripheral) -> Option<KernelClock> {
+    if let Some(number) = uart_number(peripheral) {
+        return Some(match dckcfgr2.uart_clock_source(number) {
+            UartClockSource::Apb => KernelClock::Apb,
+            UartClockSource::Sysclk => KernelClock::Sysclk,
+            UartClockSource::Hsi => KernelClock::Hsi,
+            UartClockSource::Lse => KernelClock::Lse,
+        });
+    }
+    if let Some(number) = i2c_number(peripheral) {
+        return dckcfgr2.i2c_clock_source(number).map(|source| match source {
+            I2cClockSource::Apb1 => KernelClock::Apb,
+            I2cClockSource::Sysclk => KernelClock::Sysclk,
+            I2cClockSource::Hsi => KernelClock::Hsi,
+        });
+    }
+
+    let sdmmc = |source| match source {
+        SdmmcClockSource::Clk48 => KernelClock::Clk48,
+        SdmmcClockSource::Sysclk => KernelClock::Sysclk,
+    };
+    match peripheral {
+        Peripheral::Lptim1 => {
+            Some(match dckcfgr2.lptim1_clock_source() {
+                LptimClockSource::Apb1 => KernelClock::Apb,
+                LptimClockSource::Lsi => KernelClock::Lsi,
+                LptimClockSource::Hsi => KernelClock::Hsi,
+                LptimClockSource::Lse => KernelClock::Lse,
+            })
+        }
+        #[cfg(has_cec)]
+        Peripheral::Cec => {
+            Some(match dckcfgr2.cec_clock_source() {
+                CecClockSource::Lse => KernelClock::Lse,
+                CecClockSource::HsiDiv488 => KernelClock::HsiDiv488,
+            })
+        }
+        Peripheral::OtgFs | Peripheral::Rng => {
+            Some(match dckcfgr2.clk48_source() {
+                Clk48Source::Pll => KernelClock::Pll,
+                Clk48Source::PllSai => KernelClock::PllSai,
+            })
+        }
+        Peripheral::Sdmmc1 => Some(sdmmc(dckcfgr2.sdmmc1_clock_source())),
+        #[cfg(has_sdmmc2)]
+        Peripheral::Sdmmc2 => Some(sdmmc(dckcfgr2.sdmmc2_clock_source())),
+        _ => None,
+    }
+}
+
+impl RccBank {
+    /// Selects the kernel clock of the peripheral.
+    ///
+    /// The supported sources are:
+    ///
+    /// - U(S)ARTs: `Apb`, `Sysclk`, `Hsi` and `Lse`
+    /// - I2Cs: `Apb`, `Sysclk` and `Hsi`
+    /// - LPTIM1: `Apb`, `Lsi`, `Hsi` and `Lse`
+    /// - HDMI-CEC: `Lse` and `HsiDiv488`
+    /// - USB OTG FS and RNG: `Pll` and `PllSai`, which selects the source of the whole 48 MHz
+    ///   domain, including the SDMMCs that run from it
+    /// - SDMMCs: `Clk48` and `Sysclk`
+    ///
+    /// Other combinations return `Error::InvalidKernelClock`.
+    pub fn set_kernel_clock(&mut self,
+                            peripheral: Peripheral,
+                            source: KernelClock)
+                            -> Result<(), Error> {
+        use self::KernelClock::*;
+        let invalid = Err(Error::InvalidKernelClock(peripheral, source));
+
+        if let Some(number) = uart_number(peripheral) {
+            let source = match source {
+                Apb => UartClockSource::Apb,
+                Sysclk => UartClockSource::Sysclk,
+                Hsi => UartClockSource::Hsi,
+                Lse => UartClockSource::Lse,
+                _ => return invalid,
+            };
+            self.dckcfgr2.update(|r| r.set_uart_clock_source(number, source));
+            return Ok(());
+        }
+        if let Some(number) = i2c_number(peripheral) {
+            let source = match source {
+                Apb => I2cClockSource::Apb1,
+                Sysclk => I2cClockSource::Sysclk,
+                Hsi => I2cClockSource::Hsi,
+                _ => return invalid,
+            };
+            self.dckcfgr2.update(|r| r.set_i2c_clock_source(number, source));
+            return Ok(());
+        }
+
+        let sdmmc = match source {
+            Clk48 => Some(SdmmcClockSource::Clk48),
+            Sysclk => Some(SdmmcClockSource::Sysclk),
+            _ => None,
+        };
+        match (peripheral, source) {
+            (Peripheral::Lptim1, _) => {
+                let source = match source {
+                    Apb => LptimClockSource::Apb1,
+                    Lsi => LptimClockSource::Lsi,
+                    Hsi => LptimClockSource::Hsi,
+                    Lse => LptimClockSource::Lse,
+                    _ => return invalid,
+                };
+                self.dckcfgr2.update(|r| r.set_lptim1_clock_source(source));
+            }
+            #[cfg(has_cec)]
+            (Peripheral::Cec, Lse) => {
+                self.dckcfgr2.update(|r| r.set_cec_clock_source(CecClockSource::Lse))
+            }
+            #[cfg(has_cec)]
+            (Peripheral::Cec, HsiDiv488) => {
+                self.dckcfgr2.update(|r| r.set_cec_clock_source(CecClockSource::HsiDiv488))
+            }
+            (Peripheral::OtgFs, Pll) |
+            (Peripheral::Rng, Pll) => {
+                self.dckcfgr2.update(|r| r.set_clk48_source(Clk48Source::Pll))
+            }
+            (Peripheral::OtgFs, PllSai) |
+            (Peripheral::Rng, PllSai) => {
+                self.dckcfgr2.update(|r| r.set_clk48_source(Clk48Source::PllSai))
+            }
+            (Peripheral::Sdmmc1, _) if sdmmc.is_some() => {
+                self.dckcfgr2.update(|r| r.set_sdmmc1_clock_source(sdmmc.unwrap()))
+            }
+            #[cfg(has_sdmmc2)]
+            (Peripheral::Sdmmc2, _) if sdmmc.is_some() => {
+                self.dckcfgr2.update(|r| r.set_sdmmc2_clock_source(sdmmc.unwrap()))
+            }
+            _ => return invalid,
+        }
+        Ok(())
+    }
+
+    /// Returns the kernel clock of the peripheral, or `None` if it has no kernel clock mux.
+    pub fn kernel_clock(&self, peripheral: Peripheral) -> Option<KernelClock> {
+        kernel_clock(&self.dckcfgr2.read(), peripheral)
+    }
+}
@@ -0,0 +1,206 @@
+//! LCD pixel clock (LCD_CLK)
+//!
+//! The LTDC pixel clock is the R output of the PLLSAI, divided by `PLLSAIDIVR`. The PLLSAI shares
+//! the input clock and the PLLM divider with the main PLL, so only PLLSAIN, PLLSAIR and
+//! PLLSAIDIVR are searched. PLLSAIQ is never changed. While the PLLSAI runs and clocks a SAI or
+//! the 48 MHz domain, PLLSAIN is kept as well, so that the Q and P outputs keep their
+//! frequencies. The new frequencies of all PLLSAI outputs are reported.
+
+use super::{RccBank, PllOutputs, cr};
+use super::clocks::{Error, VCO_OUTPUT_MIN, VCO_OUTPUT_MAX, PLL48_FREQ, OSCILLATOR_TIMEOUT, wait};
+use super::dckcfgr1::SaiClockSource;
+use super::dckcfgr2::Clk48Source;
+
+/// PLLSAI dividers for a pixel clock, as returned by `solve_lcd_clock`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LcdClockConfig {
+    pub n: u32,
+    /// PLLSAIP value that makes the P output exactly 48 MHz, if it was required
+    pub p: Option<u32>,
+    pub r: u32,
+    pub divr: u32,
+}
+
+impl LcdClockConfig {
+    /// Returns the pixel clock for the given PLL input frequency and PLLM value.
+    pub fn pixel_clock(&self, input: u32, m: u32) -> u32 {
+        (input as u64 * self.n as u64 / (m as u64 * self.r as u64 * self.divr as u64)) as u32
+    }
+}
+
+/// Result of `RccBank::configure_lcd_clock`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LcdClock {
+    pub config: LcdClockConfig,
+    /// Achieved pixel clock in Hz
+    pub pixel_clock: u32,
+    /// Achieved minus requested pixel clock in Hz
+    pub error: i64,
+    /// New frequencies of the PLLSAI outputs (before PLLSAIDIVR and PLLSAIDIVQ)
+    pub pllsai: PllOutputs,
+}
+
+/// Searches PLLSAI dividers for the pixel clock that is closest to `target`.
+///
+/// `input` is the PLL input frequency and `m` the PLLM value of the main PLL. The VCO output is
+/// kept between 100 and 432 MHz. If `require_48mhz` is set, only VCO frequencies that give an
+/// exact 48 MHz P output are considered, so the PLLSAI can also clock the 48 MHz domain. If `n`
+/// is given, the VCO frequency is kept and only R and DIVR are searched.
+pub fn solve_lcd_clock(input: u32,
+                       m: u32,
+                       target: u32,
+                       require_48mhz: bool,
+                       n: Option<u32>)
+                       -> Option<LcdClockConfig> {
+    if m == 0 {
+        return None;
+    }
+    let (n_min, n_max) = match n {
+        Some(n) => (n, n + 1),
+        None => (50, 433),
+    };
+    let mut best: Option<(u32, LcdClockConfig)> = None;
+
+    for n in n_min..n_max {
+        let vco = input as u64 * n as u64 / m as u64;
+        if vco < VCO_OUTPUT_MIN as u64 || vco > VCO_OUTPUT_MAX as u64 {
+            continue;
+        }
+        let p = if require_48mhz {
+            // the VCO output must be exactly 48 MHz times PLLSAIP
+            let exact = |p: u64| input as u64 * n as u64 == PLL48_FREQ as u64 * p * m as u64;
+            match [2, 4, 6, 8].iter().find(|&&p| exact(p)) {
+                Some(&p) => Some(p as u32),
+                None => continue,
+            }
+        } else {
+            None
+        };
+
+        for r in 2..8 {
+            for &divr in [2, 4, 8, 16].iter() {
+                let config = LcdClockConfig {
+                    n: n,
+                    p: p,
+                    r: r,
+                    divr: divr,
+                };
+                let freq = config.pixel_clock(input, m);
+                let deviation = if freq > target { freq - target } else { target - freq };
+                if best.map(|(best_deviation, _)| deviation < best_deviation).unwrap_or(true) {
+                    best = Some((deviation, config));
+                }
+            }
+        }
+    }
+
+    best.map(|(_, config)| config)
+}
+
+impl RccBank {
+    /// Sets up the PLLSAI for the pixel clock that is closest to `target_pixel_hz`.
+    ///
+    /// `hse` is the HSE frequency, which is needed if the PLLs run from HSE. The PLLSAI is
+    /// stopped while it is reprogrammed; this returns after it locked again. If it runs and
+    /// clocks a SAI or the 48 MHz domain, its current PLLSAIN is kept, so that those clocks keep
+    /// their frequencies, and PLLSAIP isn't changed.
+    pub fn configure_lcd_clock(&mut self,
+                               hse: Option<u32>,
+                               target_pixel_hz: u32,
+                               require_48mhz: bool)
+                               -> Result<LcdClock, Error> {
+        let input = self.pll_input(hse)?;
+        let m = self.pll_cfgr.read().pllm();
+        let n = if self.cr.read().contains(cr::PLLSAI_ON) && self.pllsai_shared() {
+            Some(self.pllsaicfgr.read().pllsain())
+        } else {
+            None
+        };
+        let config = match solve_lcd_clock(input, m, target_pixel_hz, require_48mhz, n) {
+            Some(config) => config,
+            None => return Err(Error::NoPllSaiConfiguration),
+        };
+
+        self.cr.update(|r| r.remove(cr::PLLSAI_ON));
+        if !wait(OSCILLATOR_TIMEOUT, || !self.cr.read().contains(cr::PLLSAI_RDY)) {
+            return Err(Error::PllSaiTimeout);
+        }
+        self.pllsaicfgr.update(|r| {
+            r.set_pllsain(config.n);
+            r.set_pllsair(config.r);
+            if let (Some(p), None) = (config.p, n) {
+                r.set_pllsaip(p);
+            }
+        });
+        self.dckcfgr1.update(|r| r.set_pllsai_divr(config.divr));
+        self.cr.update(|r| r.insert(cr::PLLSAI_ON));
+        if !wait(OSCILLATOR_TIMEOUT, || self.cr.read().contains(cr::PLLSAI_RDY)) {
+            return Err(Error::PllSaiTimeout);
+        }
+
+        let pixel_clock = config.pixel_clock(input, m);
+        Ok(LcdClock {
+            config: config,
+            pixel_clock: pixel_clock,
+            error: pixel_clock as i64 - target_pixel_hz as i64,
+            pllsai: self.pllsai_outputs(input),
+        })
+    }
+
+    /// Returns whether a SAI or the 48 MHz domain runs from the PLLSAI.
+    fn pllsai_shared(&self) -> bool {
+        let dckcfgr1 = self.dckcfgr1.read();
+        dckcfgr1.sai1_clock_source() == Some(SaiClockSource::PllSai) ||
+        dckcfgr1.sai2_clock_source() == Some(SaiClockSource::PllSai) ||
+        self.dckcfgr2.read().clk48_source() == Clk48Source::PllSai
+    }
+
+    /// Computes the output frequencies of the PLLSAI from its configuration.
+    fn pllsai_outputs(&self, input: u32) -> PllOutputs {
+        let m = self.pll_cfgr.read().pllm();
+        let pllsaicfgr = self.pllsaicfgr.read();
+        let vco = (input as u64 * pllsaicfgr.pllsain() as u64 / m as u64) as u32;
+        PllOutputs::new(vco, pllsaicfgr.pllsaip(), pllsaicfgr.pllsaiq(), pllsaicfgr.pllsair())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_pixel_clock() {
+        // 480x272 display of the STM32F746G Discovery board
+        let config = solve_lcd_clock(25_000_000, 25, 9_600_000, false, None).unwrap();
+        assert_eq!(config.pixel_clock(25_000_000, 25), 9_600_000);
+        assert_eq!(config.p, None);
+    }
+
+    #[test]
+    fn pixel_clock_with_48mhz() {
+        let config = solve_lcd_clock(25_000_000, 25, 9_600_000, true, None).unwrap();
+        let vco = 25_000_000 / 25 * config.n;
+        assert_eq!(vco / config.p.unwrap(), PLL48_FREQ);
+        assert!(vco >= VCO_OUTPUT_MIN && vco <= VCO_OUTPUT_MAX);
+    }
+
+    #[test]
+    fn closest_pixel_clock() {
+        // the slowest pixel clock is 100 MHz / 7 / 16
+        let config = solve_lcd_clock(25_000_000, 25, 100_000, false, None).unwrap();
+        assert_eq!(config.pixel_clock(25_000_000, 25), 100_000_000 / 7 / 16);
+    }
+
+    #[test]
+    fn fixed_n() {
+        let config = solve_lcd_clock(25_000_000, 25, 9_600_000, false, Some(192)).unwrap();
+        assert_eq!(config.n, 192);
+        // 192 MHz / 5 / 4
+        assert_eq!(config.pixel_clock(25_000_000, 25), 9_600_000);
+    }
+
+    #[test]
+    fn invalid_pllm() {
+        assert_eq!(solve_lcd_clock(25_000_000, 0, 9_600_000, false, None), None);
+    }
+}
@@ -3,38 +3,68 @@ use volatile::Volatile;
 pub mod cr;
 pub mod pll_cfgr;
 pub mod cfgr;
+pub mod cir;
 pub mod ahb1_enr;
+pub mod ahb2_enr;
 pub mod ahb3_enr;
 pub mod apb1_enr;
 pub mod apb2_enr;
 pub mod ahb1_rstr;
+pub mod ahb2_rstr;
 pub mod ahb3_rstr;
+pub mod apb1_rstr;
+pub mod apb2_rstr;
+pub mod ahb1_lpenr;
+pub mod ahb2_lpenr;
+pub mod ahb3_lpenr;
+pub mod apb1_lpenr;
+pub mod apb2_lpenr;
 pub mod pllsaicfgr;
 pub mod plli2scfgr;
 pub mod dckcfgr1;
+pub mod dckcfgr2;
+pub mod bdcr;
+pub mod csr;
+pub mod clocks;
+pub mod lcd;
+pub mod audio;
+pub mod backup_domain;
+mod peripheral;
+mod css;
+mod kernel;
+mod reset_cause;
+
+pub use self::clocks::{ClockConfig, ClockSource, Clocks, PllConfig, PllOutputs};
+pub use self::peripheral::{Peripheral, SleepClockPolicy, ClockReport};
+pub use self::css::clock_security_nmi;
+pub use self::lcd::{LcdClock, LcdClockConfig};
+pub use self::audio::{AudioClock, AudioClockSetup, SampleRateFamily};
+pub use self::kernel::KernelClock;
+pub use self::backup_domain::BackupDomain;
+pub use self::reset_cause::{ResetCause, ResetCauses};
 
 #[repr(C)]
 pub struct RccBank {
     pub cr: Volatile<cr::Register>,
     pub pll_cfgr: Volatile<pll_cfgr::Register>,
     pub cfgr: Volatile<cfgr::Register>,
-    cir: u32,
+    pub cir: Volatile<cir::Register>,
 
     // 0x10
     pub ahb1_rstr: Volatile<ahb1_rstr::Register>,
-    ahb2_rstr: u32,
+    pub ahb2_rstr: Volatile<ahb2_rstr::Register>,
     pub ahb3_rstr: Volatile<ahb3_rstr::Register>,
     _pad1: u32,
 
     // 0x20
-    apb1_rstr: u32,
-    apb2_rstr: u32,
+    pub apb1_rstr: Volatile<apb1_rstr::Register>,
+    pub apb2_rstr: Volatile<apb2_rstr::Register>,
     _pad2: u32,
     _pad3: u32,
 
     // 0x30
     pub ahb1_enr: Volatile<ahb1_enr::Register>,
-    ahb2_enr: u32,
+    pub ahb2_enr: Volatile<ahb2_enr::Register>,
     pub ahb3_enr: Volatile<ahb3_enr::Register>,
     _pad4: u32,
 
@@ -45,20 +75,20 @@ pub struct RccBank {
     _pad6: u32,
 
     // 0x50
-    ahb1_lpenr: u32,
-    ahb2_lpenr: u32,
-    ahb3_lpenr: u32,
+    pub ahb1_lpenr: Volatile<ahb1_lpenr::Register>,
+    pub ahb2_lpenr: Volatile<ahb2_lpenr::Register>,
+    pub ahb3_lpenr: Volatile<ahb3_lpenr::Register>,
     _pad7: u32,
 
     // 0x60
-    apb1_lpenr: u32,
-    apb2_lpenr: u32,
+    pub apb1_lpenr: Volatile<apb1_lpenr::Register>,
+    pub apb2_lpenr: Volatile<apb2_lpenr::Register>,
     _pad8: u32,
     _pad9: u32,
 
     // 0x70
-    bdcr: u32,
-    csr: u32,
+    pub bdcr: Volatile<bdcr::Register>,
+    pub csr: Volatile<csr::Register>,
     _pad10: u32,
     _pad11: u32,
 
@@ -69,42 +99,50 @@ pub struct RccBank {
     pub dckcfgr1: Volatile<dckcfgr1::Register>,
 
     // 0x90
-    dckcfgr2: u32,
+    pub dckcfgr2: Volatile<dckcfgr2::Register>,
 }
 
 impl RccBank {
+    /// Enables the clocks of all ports that the chip variant has.
     pub fn enable_all_gpio_ports(&mut self) {
-        self.ahb1_enr.update(|r| {
-            r.insert(ahb1_enr::GPIO_A_ENABLE | ahb1_enr::GPIO_B_ENABLE | ahb1_enr::GPIO_C_ENABLE |
-                     ahb1_enr::GPIO_D_ENABLE |
-                     ahb1_enr::GPIO_E_ENABLE | ahb1_enr::GPIO_F_ENABLE |
-                     ahb1_enr::GPIO_G_ENABLE |
-                     ahb1_enr::GPIO_H_ENABLE |
-                     ahb1_enr::GPIO_I_ENABLE |
-                     ahb1_enr::GPIO_J_ENABLE | ahb1_enr::GPIO_K_ENABLE)
-        });
+        let ports = ahb1_enr::GPIO_A_ENABLE | ahb1_enr::GPIO_B_ENABLE | ahb1_enr::GPIO_C_ENABLE |
+                    ahb1_enr::GPIO_D_ENABLE | ahb1_enr::GPIO_H_ENABLE;
+        #[cfg(has_gpio_e)]
+        let ports = ports | ahb1_enr::GPIO_E_ENABLE;
+        #[cfg(has_gpio_f)]
+        let ports = ports | ahb1_enr::GPIO_F_ENABLE;
+        #[cfg(has_gpio_g)]
+        let ports = ports | ahb1_enr::GPIO_G_ENABLE;
+        #[cfg(has_gpio_i)]
+        let ports = ports | ahb1_enr::GPIO_I_ENABLE;
+        #[cfg(has_gpio_j)]
+        let ports = ports | ahb1_enr::GPIO_J_ENABLE;
+        #[cfg(has_gpio_k)]
+        let ports = ports | ahb1_enr::GPIO_K_ENABLE;
+
+        self.ahb1_enr.update(|r| r.insert(ports));
     }
 
+    /// Resets all ports that the chip variant has.
     pub fn reset_all_gpio_ports(&mut self) {
+        let ports = ahb1_rstr::GPIO_A_RESET | ahb1_rstr::GPIO_B_RESET | ahb1_rstr::GPIO_C_RESET |
+                    ahb1_rstr::GPIO_D_RESET | ahb1_rstr::GPIO_H_RESET;
+        #[cfg(has_gpio_e)]
+        let ports = ports | ahb1_rstr::GPIO_E_RESET;
+        #[cfg(has_gpio_f)]
+        let ports = ports | ahb1_rstr::GPIO_F_RESET;
+        #[cfg(has_gpio_g)]
+        let ports = ports | ahb1_rstr::GPIO_G_RESET;
+        #[cfg(has_gpio_i)]
+        let ports = ports | ahb1_rstr::GPIO_I_RESET;
+        #[cfg(has_gpio_j)]
+        let ports = ports | ahb1_rstr::GPIO_J_RESET;
+        #[cfg(has_gpio_k)]
+        let ports = ports | ahb1_rstr::GPIO_K_RESET;
+
         // set reset bits
-        self.ahb1_rstr.update(|r| {
-            r.insert(ahb1_rstr::GPIO_A_RESET | ahb1_rstr::GPIO_B_RESET | ahb1_rstr::GPIO_C_RESET |
-                     ahb1_rstr::GPIO_D_RESET |
-                     ahb1_rstr::GPIO_E_RESET | ahb1_rstr::GPIO_F_RESET |
-                     ahb1_rstr::GPIO_G_RESET |
-                     ahb1_rstr::GPIO_H_RESET |
-                     ahb1_rstr::GPIO_I_RESET |
-                     ahb1_rstr::GPIO_J_RESET | ahb1_rstr::GPIO_K_RESET)
-        });
+        self.ahb1_rstr.update(|r| r.insert(ports));
         // clear reset bits
-        self.ahb1_rstr.update(|r| {
-            r.remove(ahb1_rstr::GPIO_A_RESET | ahb1_rstr::GPIO_B_RESET | ahb1_rstr::GPIO_C_RESET |
-                     ahb1_rstr::GPIO_D_RESET |
-                     ahb1_rstr::GPIO_E_RESET | ahb1_rstr::GPIO_F_RESET |
-                     ahb1_rstr::GPIO_G_RESET |
-                     ahb1_rstr::GPIO_H_RESET |
-                     ahb1_rstr::GPIO_I_RESET |
-                     ahb1_rstr::GPIO_J_RESET | ahb1_rstr::GPIO_K_RESET)
-        });
+        self.ahb1_rstr.update(|r| r.remove(ports));
     }
 }
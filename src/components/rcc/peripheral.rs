@@ -0,0 +1,614 @@
+//! Peripheral clock enable and reset control
+//!
+//! Every peripheral has an enable bit in one of the `*_enr` registers of its bus and, with a few
+//! exceptions, a reset bit at the same position in the matching `*_rstr` register. The bit that
+//! keeps the clock enabled in Sleep mode is also at the same position in the `*_lpenr` register.
+
+use core::fmt;
+use super::{RccBank, ahb1_enr, ahb1_rstr, ahb2_enr, ahb2_rstr, ahb3_enr, ahb3_rstr, apb1_enr,
+            apb1_rstr, apb2_enr, apb2_rstr, ahb1_lpenr, ahb2_lpenr, ahb3_lpenr, apb1_lpenr,
+            apb2_lpenr};
+
+/// Peripheral with a clock enable bit in the RCC
+///
+/// Peripherals that the selected chip variant lacks don't exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Peripheral {
+    // AHB1
+    GpioA,
+    GpioB,
+    GpioC,
+    GpioD,
+    #[cfg(has_gpio_e)]
+    GpioE,
+    #[cfg(has_gpio_f)]
+    GpioF,
+    #[cfg(has_gpio_g)]
+    GpioG,
+    GpioH,
+    #[cfg(has_gpio_i)]
+    GpioI,
+    #[cfg(has_gpio_j)]
+    GpioJ,
+    #[cfg(has_gpio_k)]
+    GpioK,
+    Crc,
+    BkpSram,
+    DtcmRam,
+    Dma1,
+    Dma2,
+    #[cfg(has_dma2d)]
+    Dma2d,
+    #[cfg(has_eth)]
+    EthMac,
+    #[cfg(has_eth)]
+    EthMacTx,
+    #[cfg(has_eth)]
+    EthMacRx,
+    #[cfg(has_eth)]
+    EthMacPtp,
+    OtgHs,
+    OtgHsUlpi,
+
+    // AHB2
+    #[cfg(has_dcmi)]
+    Dcmi,
+    #[cfg(has_jpeg)]
+    Jpeg,
+    #[cfg(has_cryp)]
+    Cryp,
+    #[cfg(has_hash)]
+    Hash,
+    Rng,
+    OtgFs,
+
+    // AHB3
+    Fmc,
+    Qspi,
+
+    // APB1
+    Tim2,
+    Tim3,
+    Tim4,
+    Tim5,
+    Tim6,
+    Tim7,
+    Tim12,
+    Tim13,
+    Tim14,
+    Lptim1,
+    Wwdg,
+    #[cfg(has_can3)]
+    Can3,
+    Spi2,
+    Spi3,
+    #[cfg(has_spdifrx)]
+    SpdifRx,
+    Usart2,
+    Usart3,
+    Uart4,
+    Uart5,
+    I2c1,
+    I2c2,
+    I2c3,
+    #[cfg(has_i2c4)]
+    I2c4,
+    Can1,
+    #[cfg(has_can2)]
+    Can2,
+    #[cfg(has_cec)]
+    Cec,
+    Pwr,
+    Dac,
+    Uart7,
+    Uart8,
+
+    // APB2
+    Tim1,
+    Tim8,
+    Usart1,
+    Usart6,
+    #[cfg(has_sdmmc2)]
+    Sdmmc2,
+    Adc1,
+    Adc2,
+    Adc3,
+    Sdmmc1,
+    Spi1,
+    Spi4,
+    Syscfg,
+    Tim9,
+    Tim10,
+    Tim11,
+    Spi5,
+    #[cfg(has_spi6)]
+    Spi6,
+    Sai1,
+    Sai2,
+    #[cfg(has_ltdc)]
+    Ltdc,
+    #[cfg(has_dsi)]
+    Dsi,
+    #[cfg(has_dfsdm1)]
+    Dfsdm1,
+    #[cfg(has_mdio)]
+    Mdio,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(usize)]
+enum Bus {
+    Ahb1,
+    Ahb2,
+    Ahb3,
+    Apb1,
+    Apb2,
+}
+
+/// All peripherals of the selected chip variant
+static PERIPHERALS: &'static [Peripheral] = &[
+    Peripheral::GpioA,
+    Peripheral::GpioB,
+    Peripheral::GpioC,
+    Peripheral::GpioD,
+    #[cfg(has_gpio_e)]
+    Peripheral::GpioE,
+    #[cfg(has_gpio_f)]
+    Peripheral::GpioF,
+    #[cfg(has_gpio_g)]
+    Peripheral::GpioG,
+    Peripheral::GpioH,
+    #[cfg(has_gpio_i)]
+    Peripheral::GpioI,
+    #[cfg(has_gpio_j)]
+    Peripheral::GpioJ,
+    #[cfg(has_gpio_k)]
+    Peripheral::GpioK,
+    Peripheral::Crc,
+    Peripheral::BkpSram,
+    Peripheral::DtcmRam,
+    Peripheral::Dma1,
+    Peripheral::Dma2,
+    #[cfg(has_dma2d)]
+    Peripheral::Dma2d,
+    #[cfg(has_eth)]
+    Peripheral::EthMac,
+    #[cfg(has_eth)]
+    Peripheral::EthMacTx,
+    #[cfg(has_eth)]
+    Peripheral::EthMacRx,
+    #[cfg(has_eth)]
+    Peripheral::EthMacPtp,
+    Peripheral::OtgHs,
+    Peripheral::OtgHsUlpi,
+    #[cfg(has_dcmi)]
+    Peripheral::Dcmi,
+    #[cfg(has_jpeg)]
+    Peripheral::Jpeg,
+    #[cfg(has_cryp)]
+    Peripheral::Cryp,
+    #[cfg(has_hash)]
+    Peripheral::Hash,
+    Peripheral::Rng,
+    Peripheral::OtgFs,
+    Peripheral::Fmc,
+    Peripheral::Qspi,
+    Peripheral::Tim2,
+    Peripheral::Tim3,
+    Peripheral::Tim4,
+    Peripheral::Tim5,
+    Peripheral::Tim6,
+    Peripheral::Tim7,
+    Peripheral::Tim12,
+    Peripheral::Tim13,
+    Peripheral::Tim14,
+    Peripheral::Lptim1,
+    Peripheral::Wwdg,
+    #[cfg(has_can3)]
+    Peripheral::Can3,
+    Peripheral::Spi2,
+    Peripheral::Spi3,
+    #[cfg(has_spdifrx)]
+    Peripheral::SpdifRx,
+    Peripheral::Usart2,
+    Peripheral::Usart3,
+    Peripheral::Uart4,
+    Peripheral::Uart5,
+    Peripheral::I2c1,
+    Peripheral::I2c2,
+    Peripheral::I2c3,
+    #[cfg(has_i2c4)]
+    Peripheral::I2c4,
+    Peripheral::Can1,
+    #[cfg(has_can2)]
+    Peripheral::Can2,
+    #[cfg(has_cec)]
+    Peripheral::Cec,
+    Peripheral::Pwr,
+    Peripheral::Dac,
+    Peripheral::Uart7,
+    Peripheral::Uart8,
+    Peripheral::Tim1,
+    Peripheral::Tim8,
+    Peripheral::Usart1,
+    Peripheral::Usart6,
+    #[cfg(has_sdmmc2)]
+    Peripheral::Sdmmc2,
+    Peripheral::Adc1,
+    Peripheral::Adc2,
+    Peripheral::Adc3,
+    Peripheral::Sdmmc1,
+    Peripheral::Spi1,
+    Peripheral::Spi4,
+    Peripheral::Syscfg,
+    Peripheral::Tim9,
+    Peripheral::Tim10,
+    Peripheral::Tim11,
+    Peripheral::Spi5,
+    #[cfg(has_spi6)]
+    Peripheral::Spi6,
+    Peripheral::Sai1,
+    Peripheral::Sai2,
+    #[cfg(has_ltdc)]
+    Peripheral::Ltdc,
+    #[cfg(has_dsi)]
+    Peripheral::Dsi,
+    #[cfg(has_dfsdm1)]
+    Peripheral::Dfsdm1,
+    #[cfg(has_mdio)]
+    Peripheral::Mdio,
+];
+
+impl Peripheral {
+    /// Returns all peripherals that the selected chip variant has.
+    pub fn all() -> &'static [Peripheral] {
+        PERIPHERALS
+    }
+
+    /// Returns the bus of the peripheral and the position of its enable bit.
+    fn enable_bit(self) -> (Bus, u8) {
+        use self::Peripheral::*;
+        match self {
+            GpioA => (Bus::Ahb1, 0),
+            GpioB => (Bus::Ahb1, 1),
+            GpioC => (Bus::Ahb1, 2),
+            GpioD => (Bus::Ahb1, 3),
+            #[cfg(has_gpio_e)]
+            GpioE => (Bus::Ahb1, 4),
+            #[cfg(has_gpio_f)]
+            GpioF => (Bus::Ahb1, 5),
+            #[cfg(has_gpio_g)]
+            GpioG => (Bus::Ahb1, 6),
+            GpioH => (Bus::Ahb1, 7),
+            #[cfg(has_gpio_i)]
+            GpioI => (Bus::Ahb1, 8),
+            #[cfg(has_gpio_j)]
+            GpioJ => (Bus::Ahb1, 9),
+            #[cfg(has_gpio_k)]
+            GpioK => (Bus::Ahb1, 10),
+            Crc => (Bus::Ahb1, 12),
+            BkpSram => (Bus::Ahb1, 18),
+            DtcmRam => (Bus::Ahb1, 20),
+            Dma1 => (Bus::Ahb1, 21),
+            Dma2 => (Bus::Ahb1, 22),
+            #[cfg(has_dma2d)]
+            Dma2d => (Bus::Ahb1, 23),
+            #[cfg(has_eth)]
+            EthMac => (Bus::Ahb1, 25),
+            #[cfg(has_eth)]
+            EthMacTx => (Bus::Ahb1, 26),
+            #[cfg(has_eth)]
+            EthMacRx => (Bus::Ahb1, 27),
+            #[cfg(has_eth)]
+            EthMacPtp => (Bus::Ahb1, 28),
+            OtgHs => (Bus::Ahb1, 29),
+            OtgHsUlpi => (Bus::Ahb1, 30),
+            #[cfg(has_dcmi)]
+            Dcmi => (Bus::Ahb2, 0),
+            #[cfg(has_jpeg)]
+            Jpeg => (Bus::Ahb2, 1),
+            #[cfg(has_cryp)]
+            Cryp => (Bus::Ahb2, 4),
+            #[cfg(has_hash)]
+            Hash => (Bus::Ahb2, 5),
+            Rng => (Bus::Ahb2, 6),
+            OtgFs => (Bus::Ahb2, 7),
+            Fmc => (Bus::Ahb3, 0),
+            Qspi => (Bus::Ahb3, 1),
+            Tim2 => (Bus::Apb1, 0),
+            Tim3 => (Bus::Apb1, 1),
+            Tim4 => (Bus::Apb1, 2),
+            Tim5 => (Bus::Apb1, 3),
+            Tim6 => (Bus::Apb1, 4),
+            Tim7 => (Bus::Apb1, 5),
+            Tim12 => (Bus::Apb1, 6),
+            Tim13 => (Bus::Apb1, 7),
+            Tim14 => (Bus::Apb1, 8),
+            Lptim1 => (Bus::Apb1, 9),
+            Wwdg => (Bus::Apb1, 11),
+            #[cfg(has_can3)]
+            Can3 => (Bus::Apb1, 13),
+            Spi2 => (Bus::Apb1, 14),
+            Spi3 => (Bus::Apb1, 15),
+            #[cfg(has_spdifrx)]
+            SpdifRx => (Bus::Apb1, 16),
+            Usart2 => (Bus::Apb1, 17),
+            Usart3 => (Bus::Apb1, 18),
+            Uart4 => (Bus::Apb1, 19),
+            Uart5 => (Bus::Apb1, 20),
+            I2c1 => (Bus::Apb1, 21),
+            I2c2 => (Bus::Apb1, 22),
+            I2c3 => (Bus::Apb1, 23),
+            #[cfg(has_i2c4)]
+            I2c4 => (Bus::Apb1, 24),
+            Can1 => (Bus::Apb1, 25),
+            #[cfg(has_can2)]
+            Can2 => (Bus::Apb1, 26),
+            #[cfg(has_cec)]
+            Cec => (Bus::Apb1, 27),
+            Pwr => (Bus::Apb1, 28),
+            Dac => (Bus::Apb1, 29),
+            Uart7 => (Bus::Apb1, 30),
+            Uart8 => (Bus::Apb1, 31),
+            Tim1 => (Bus::Apb2, 0),
+            Tim8 => (Bus::Apb2, 1),
+            Usart1 => (Bus::Apb2, 4),
+            Usart6 => (Bus::Apb2, 5),
+            #[cfg(has_sdmmc2)]
+            Sdmmc2 => (Bus::Apb2, 7),
+            Adc1 => (Bus::Apb2, 8),
+            Adc2 => (Bus::Apb2, 9),
+            Adc3 => (Bus::Apb2, 10),
+            Sdmmc1 => (Bus::Apb2, 11),
+            Spi1 => (Bus::Apb2, 12),
+            Spi4 => (Bus::Apb2, 13),
+            Syscfg => (Bus::Apb2, 14),
+            Tim9 => (Bus::Apb2, 16),
+            Tim10 => (Bus::Apb2, 17),
+            Tim11 => (Bus::Apb2, 18),
+            Spi5 => (Bus::Apb2, 20),
+            #[cfg(has_spi6)]
+            Spi6 => (Bus::Apb2, 21),
+            Sai1 => (Bus::Apb2, 22),
+            Sai2 => (Bus::Apb2, 23),
+            #[cfg(has_ltdc)]
+            Ltdc => (Bus::Apb2, 26),
+            #[cfg(has_dsi)]
+            Dsi => (Bus::Apb2, 27),
+            #[cfg(has_dfsdm1)]
+            Dfsdm1 => (Bus::Apb2, 29),
+            #[cfg(has_mdio)]
+            Mdio => (Bus::Apb2, 30),
+        }
+    }
+
+    /// Returns the position of the reset bit of the peripheral, if it has one.
+    ///
+    /// The reset bit is at the position of the enable bit, except for the peripherals whose
+    /// clocks share a reset bit.
+    fn reset_bit(self) -> Option<u8> {
+        use self::Peripheral::*;
+        match self {
+            BkpSram | DtcmRam => None,
+            #[cfg(has_eth)]
+            EthMacTx | EthMacRx | EthMacPtp => Some(25),
+            OtgHsUlpi => Some(29),
+            // ADC_RESET resets all three ADCs
+            Adc2 | Adc3 => Some(8),
+            _ => Some(self.enable_bit().1),
+        }
+    }
+}
+
+impl RccBank {
+    /// Enables the clock of the peripheral.
+    ///
+    /// The register is read back after the write, so that the clock is running before the
+    /// peripheral is accessed.
+    pub fn enable(&mut self, peripheral: Peripheral) {
+        self.set_enabled(peripheral, true);
+    }
+
+    /// Disables the clock of the peripheral.
+    pub fn disable(&mut self, peripheral: Peripheral) {
+        self.set_enabled(peripheral, false);
+    }
+
+    /// Returns whether the clock of the peripheral is enabled.
+    pub fn is_enabled(&self, peripheral: Peripheral) -> bool {
+        let (bus, bit) = peripheral.enable_bit();
+        let bits = match bus {
+            Bus::Ahb1 => self.ahb1_enr.read().bits(),
+            Bus::Ahb2 => self.ahb2_enr.read().bits(),
+            Bus::Ahb3 => self.ahb3_enr.read().bits(),
+            Bus::Apb1 => self.apb1_enr.read().bits(),
+            Bus::Apb2 => self.apb2_enr.read().bits(),
+        };
+        bits & (1 << bit) != 0
+    }
+
+    /// Resets the peripheral by setting and clearing its reset bit.
+    ///
+    /// Some peripherals share a reset bit: resetting one ADC resets all three, and resetting one
+    /// of the Ethernet or USB OTG HS clocks resets the whole controller. The backup SRAM and the
+    /// DTCM RAM have no reset bit, so nothing happens for them.
+    pub fn reset(&mut self, peripheral: Peripheral) {
+        let bit = match peripheral.reset_bit() {
+            Some(bit) => bit,
+            None => return,
+        };
+        let mask = 1 << bit;
+        match peripheral.enable_bit().0 {
+            Bus::Ahb1 => {
+                let bits = ahb1_rstr::Register::from_bits_truncate(mask);
+                self.ahb1_rstr.update(|r| r.insert(bits));
+                self.ahb1_rstr.update(|r| r.remove(bits));
+            }
+            Bus::Ahb2 => {
+                let bits = ahb2_rstr::Register::from_bits_truncate(mask);
+                self.ahb2_rstr.update(|r| r.insert(bits));
+                self.ahb2_rstr.update(|r| r.remove(bits));
+            }
+            Bus::Ahb3 => {
+                let bits = ahb3_rstr::Register::from_bits_truncate(mask);
+                self.ahb3_rstr.update(|r| r.insert(bits));
+                self.ahb3_rstr.update(|r| r.remove(bits));
+            }
+            Bus::Apb1 => {
+                let bits = apb1_rstr::Register::from_bits_truncate(mask);
+                self.apb1_rstr.update(|r| r.insert(bits));
+                self.apb1_rstr.update(|r| r.remove(bits));
+            }
+            Bus::Apb2 => {
+                let bits = apb2_rstr::Register::from_bits_truncate(mask);
+                self.apb2_rstr.update(|r| r.insert(bits));
+                self.apb2_rstr.update(|r| r.remove(bits));
+            }
+        }
+    }
+
+    fn set_enabled(&mut self, peripheral: Peripheral, value: bool) {
+        let (bus, bit) = peripheral.enable_bit();
+        let mask = 1 << bit;
+        match bus {
+            Bus::Ahb1 => {
+                let bits = ahb1_enr::Register::from_bits_truncate(mask);
+                self.ahb1_enr.update(|r| if value { r.insert(bits) } else { r.remove(bits) });
+                self.ahb1_enr.read();
+            }
+            Bus::Ahb2 => {
+                let bits = ahb2_enr::Register::from_bits_truncate(mask);
+                self.ahb2_enr.update(|r| if value { r.insert(bits) } else { r.remove(bits) });
+                self.ahb2_enr.read();
+            }
+            Bus::Ahb3 => {
+                let bits = ahb3_enr::Register::from_bits_truncate(mask);
+                self.ahb3_enr.update(|r| if value { r.insert(bits) } else { r.remove(bits) });
+                self.ahb3_enr.read();
+            }
+            Bus::Apb1 => {
+                let bits = apb1_enr::Register::from_bits_truncate(mask);
+                self.apb1_enr.update(|r| if value { r.insert(bits) } else { r.remove(bits) });
+                self.apb1_enr.read();
+            }
+            Bus::Apb2 => {
+                let bits = apb2_enr::Register::from_bits_truncate(mask);
+                self.apb2_enr.update(|r| if value { r.insert(bits) } else { r.remove(bits) });
+                self.apb2_enr.read();
+            }
+        }
+    }
+
+    /// Returns whether the clock of the peripheral stays enabled in Sleep mode.
+    ///
+    /// The clock is only running in Sleep mode if it is also enabled through `enable`.
+    pub fn is_enabled_in_sleep(&self, peripheral: Peripheral) -> bool {
+        let (bus, bit) = peripheral.enable_bit();
+        self.sleep_bits(bus) & (1 << bit) != 0
+    }
+
+    /// Selects the peripherals whose clocks stay enabled in Sleep mode.
+    ///
+    /// The low power enable bits of all peripherals are written at once. The bits of the
+    /// memories that aren't a `Peripheral` (flash interface, AXI, SRAM1 and SRAM2) keep their
+    /// value.
+    pub fn apply_sleep_policy(&mut self, policy: &SleepClockPolicy) {
+        let mut all = [0; 5];
+        for &peripheral in Peripheral::all() {
+            let (bus, bit) = peripheral.enable_bit();
+            all[bus as usize] |= 1 << bit;
+        }
+        let bits = |bus: Bus, current: u32| {
+            (current & !all[bus as usize]) | policy.bits[bus as usize]
+        };
+
+        self.ahb1_lpenr.update(|r| {
+            *r = ahb1_lpenr::Register::from_bits_truncate(bits(Bus::Ahb1, r.bits()))
+        });
+        self.ahb2_lpenr.update(|r| {
+            *r = ahb2_lpenr::Register::from_bits_truncate(bits(Bus::Ahb2, r.bits()))
+        });
+        self.ahb3_lpenr.update(|r| {
+            *r = ahb3_lpenr::Register::from_bits_truncate(bits(Bus::Ahb3, r.bits()))
+        });
+        self.apb1_lpenr.update(|r| {
+            *r = apb1_lpenr::Register::from_bits_truncate(bits(Bus::Apb1, r.bits()))
+        });
+        self.apb2_lpenr.update(|r| {
+            *r = apb2_lpenr::Register::from_bits_truncate(bits(Bus::Apb2, r.bits()))
+        });
+    }
+
+    /// Returns a table of the peripherals whose clocks are enabled, showing which of them stay
+    /// enabled in Sleep mode.
+    pub fn clock_report(&self) -> ClockReport {
+        ClockReport(self)
+    }
+
+    fn sleep_bits(&self, bus: Bus) -> u32 {
+        match bus {
+            Bus::Ahb1 => self.ahb1_lpenr.read().bits(),
+            Bus::Ahb2 => self.ahb2_lpenr.read().bits(),
+            Bus::Ahb3 => self.ahb3_lpenr.read().bits(),
+            Bus::Apb1 => self.apb1_lpenr.read().bits(),
+            Bus::Apb2 => self.apb2_lpenr.read().bits(),
+        }
+    }
+}
+
+/// Set of peripherals whose clocks stay enabled in Sleep mode
+///
+/// After reset, nearly all clocks stay enabled in Sleep mode. A policy starts out with no
+/// peripherals, so only the listed ones keep running:
+///
+/// ```ignore
+/// let policy = SleepClockPolicy::new().keep(Peripheral::Usart1).keep(Peripheral::Tim2);
+/// rcc.apply_sleep_policy(&policy);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SleepClockPolicy {
+    bits: [u32; 5],
+}
+
+impl SleepClockPolicy {
+    /// Creates a policy that stops the clocks of all peripherals in Sleep mode.
+    pub fn new() -> SleepClockPolicy {
+        SleepClockPolicy { bits: [0; 5] }
+    }
+
+    /// Keeps the clock of the peripheral enabled in Sleep mode.
+    pub fn keep(mut self, peripheral: Peripheral) -> SleepClockPolicy {
+        let (bus, bit) = peripheral.enable_bit();
+        self.bits[bus as usize] |= 1 << bit;
+        self
+    }
+
+    /// Returns whether the policy keeps the clock of the peripheral enabled in Sleep mode.
+    pub fn keeps(&self, peripheral: Peripheral) -> bool {
+        let (bus, bit) = peripheral.enable_bit();
+        self.bits[bus as usize] & (1 << bit) != 0
+    }
+}
+
+/// Prints the peripherals whose clocks are enabled and whether they stay enabled in Sleep mode.
+///
+/// Created by `RccBank::clock_report`.
+pub struct ClockReport<'a>(&'a RccBank);
+
+impl<'a> fmt::Display for ClockReport<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "sleep  peripheral")?;
+        for &peripheral in Peripheral::all() {
+            if !self.0.is_enabled(peripheral) {
+                continue;
+            }
+            let sleep = if self.0.is_enabled_in_sleep(peripheral) {
+                "on"
+            } else {
+                "off"
+            };
+            writeln!(f, "{:<5}  {:?}", sleep, peripheral)?;
+        }
+        Ok(())
+    }
+}
@@ -19,6 +19,11 @@ impl Register {
         (self.0.get_range(16..18) + 1) * 2
     }
 
+    /// Returns whether the PLLs take their input from the HSE oscillator instead of HSI.
+    pub fn pllsrc(&self) -> bool {
+        self.0.get_bit(22)
+    }
+
     pub fn pllq(&self) -> u32 {
         self.0.get_range(24..28)
     }
@@ -13,6 +13,9 @@ impl Register {
         assert!(number >= 50 && number < 433);
         self.0.set_range(6..15, number);
     }
+    pub fn pllsaip(&self) -> u32 {
+        (self.0.get_range(16..18) + 1) * 2
+    }
     pub fn set_pllsaip(&mut self, number: u32) {
         let value = match number {
             2 => 0b00,
@@ -30,6 +33,9 @@ impl Register {
         assert!(number >= 2 && number < 16);
         self.0.set_range(24..28, number);
     }
+    pub fn pllsair(&self) -> u32 {
+        self.0.get_range(28..31)
+    }
     pub fn set_pllsair(&mut self, number: u32) {
         assert!(number >= 2 && number < 8);
         self.0.set_range(28..31, number);
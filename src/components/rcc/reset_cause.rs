@@ -0,0 +1,106 @@
+//! Reset cause flags in RCC_CSR
+//!
+//! The flags accumulate over resets until they are cleared, and a reset usually sets more than
+//! one of them: every reset also drives the NRST pin, so the pin reset flag is set as well, and a
+//! power-on sets the brown-out flag. `ResetCauses::primary` picks the most specific cause.
+
+use core::fmt;
+use super::{RccBank, csr};
+
+/// Cause of a reset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetCause {
+    /// Power-on or power-down reset
+    PowerOn,
+    /// Supply voltage dropped below the brown-out threshold
+    BrownOut,
+    /// External reset through the NRST pin
+    Pin,
+    /// Reset requested by software through `SCB_AIRCR`
+    Software,
+    IndependentWatchdog,
+    WindowWatchdog,
+    /// Illegal entry into stop or standby mode, see the option bytes
+    LowPower,
+}
+
+/// All reset flags that were set, as returned by `RccBank::reset_cause`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResetCauses(csr::Register);
+
+impl ResetCauses {
+    /// Returns whether the flag of the cause was set.
+    pub fn contains(&self, cause: ResetCause) -> bool {
+        self.0.contains(flag(cause))
+    }
+
+    /// Returns the most specific cause, or `None` if no flag was set.
+    ///
+    /// Watchdog, low-power and software resets take precedence over power-on, which takes
+    /// precedence over brown-out and pin resets, since those flags are set along with the others.
+    pub fn primary(&self) -> Option<ResetCause> {
+        PRIORITY.iter().cloned().find(|&cause| self.contains(cause))
+    }
+
+    /// Returns whether a watchdog caused the reset.
+    pub fn is_watchdog(&self) -> bool {
+        self.contains(ResetCause::IndependentWatchdog) ||
+        self.contains(ResetCause::WindowWatchdog)
+    }
+
+    /// Returns the raw flags of RCC_CSR.
+    pub fn bits(&self) -> u32 {
+        self.0.bits()
+    }
+}
+
+/// Lists the causes whose flags were set, most specific first.
+impl fmt::Display for ResetCauses {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        for &cause in PRIORITY.iter().filter(|&&cause| self.contains(cause)) {
+            if !first {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:?}", cause)?;
+            first = false;
+        }
+        if first {
+            write!(f, "none")?;
+        }
+        Ok(())
+    }
+}
+
+static PRIORITY: [ResetCause; 7] = [ResetCause::LowPower,
+                                    ResetCause::IndependentWatchdog,
+                                    ResetCause::WindowWatchdog,
+                                    ResetCause::Software,
+                                    ResetCause::PowerOn,
+                                    ResetCause::BrownOut,
+                                    ResetCause::Pin];
+
+fn flag(cause: ResetCause) -> csr::Register {
+    match cause {
+        ResetCause::PowerOn => csr::POR_RST_F,
+        ResetCause::BrownOut => csr::BOR_RST_F,
+        ResetCause::Pin => csr::PIN_RST_F,
+        ResetCause::Software => csr::SFT_RST_F,
+        ResetCause::IndependentWatchdog => csr::IWDG_RST_F,
+        ResetCause::WindowWatchdog => csr::WWDG_RST_F,
+        ResetCause::LowPower => csr::LPWR_RST_F,
+    }
+}
+
+impl RccBank {
+    /// Returns the reset flags and clears them, so that the next reset starts from none.
+    ///
+    /// The startup code calls this before `main`, see `runtime::reset_causes`.
+    pub fn reset_cause(&mut self) -> ResetCauses {
+        let flags = self.csr.read() &
+                    (csr::POR_RST_F | csr::BOR_RST_F | csr::PIN_RST_F | csr::SFT_RST_F |
+                     csr::IWDG_RST_F | csr::WWDG_RST_F | csr::LPWR_RST_F);
+        self.csr.update(|r| r.insert(csr::RMVF));
+        ResetCauses(flags)
+    }
+}
@@ -0,0 +1,25 @@
+//! SYSCFG external interrupt configuration registers (SYSCFG_EXTICR1 to SYSCFG_EXTICR4)
+
+use bit_field::BitField;
+
+/// Register
+///
+/// Each register selects the source port of four EXTI lines: `SYSCFG_EXTICR1` of lines 0–3,
+/// `SYSCFG_EXTICR2` of lines 4–7 and so on.
+#[derive(Debug, Clone, Copy)]
+pub struct Register(BitField<u32>);
+
+impl Register {
+    /// Returns the source port of EXTI line `line` (0 = port A, …, 10 = port K)
+    pub fn port(&self, line: u8) -> u32 {
+        let offset = (line % 4) * 4;
+        self.0.get_range(offset..offset + 4)
+    }
+
+    /// Selects the source port of EXTI line `line` (0 = port A, …, 10 = port K)
+    pub fn set_port(&mut self, line: u8, port: u32) {
+        assert!(port < 11);
+        let offset = (line % 4) * 4;
+        self.0.set_range(offset..offset + 4, port);
+    }
+}
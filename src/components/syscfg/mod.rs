@@ -0,0 +1,18 @@
+//! System configuration controller (SYSCFG)
+
+use volatile::Volatile;
+
+pub mod exticr;
+
+#[repr(C)]
+pub struct SyscfgBank {
+    memrmp: u32,
+    pmc: u32,
+    /// External interrupt configuration registers 1 to 4
+    pub exticr: [Volatile<exticr::Register>; 4],
+
+    // 0x18
+    _pad1: u32,
+    _pad2: u32,
+    cmpcr: u32,
+}
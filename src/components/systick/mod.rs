@@ -1,6 +1,6 @@
 // see http://infocenter.arm.com/help/index.jsp?topic=/com.arm.doc.dui0646b/Bhccjgga.html
 
-use components::rcc::RccBank;
+use components::rcc::Clocks;
 use volatile::Volatile;
 
 pub mod csr;
@@ -20,13 +20,12 @@ pub struct SysTickBank {
 }
 
 impl SysTickBank {
-    pub fn setup(&'static mut self, rcc: &RccBank, enable_interrupt: bool) -> SysTick {
+    /// Sets up one tick per millisecond, based on the processor clock (HCLK).
+    ///
+    /// `clocks` can be obtained from `RccBank::clocks`.
+    pub fn setup(&'static mut self, clocks: &Clocks, enable_interrupt: bool) -> SysTick {
         // Progam SysTick
-        let pll_cfgr = rcc.pll_cfgr.read();
-        let pllm = pll_cfgr.pllm();
-        let plln = pll_cfgr.plln();
-        let pllp = pll_cfgr.pllp();
-        self.rvr.update(|r| r.set(25 * 1000 / pllm * plln / pllp - 1)); // hse runs at 25 MHz
+        self.rvr.update(|r| r.set(clocks.hclk() / 1000 - 1));
         self.cvr.update(|r| r.clear());
 
         let mut flags = self::csr::CLKSOURCE | self::csr::ENABLE;
@@ -47,4 +46,25 @@ impl SysTick {
             while !self.0.csr.read().contains(csr::COUNTFLAG) {}
         }
     }
+
+    /// Waits for the given number of microseconds by following the current value register.
+    ///
+    /// Assumes that the reload value is set up for one tick per millisecond, like `setup` does.
+    pub fn busy_wait_us(&self, microseconds: u32) {
+        let ticks_per_ms = self.0.rvr.read().value() as u64 + 1;
+        let ticks = microseconds as u64 * ticks_per_ms / 1000;
+
+        let mut elapsed = 0;
+        let mut previous = self.0.cvr.read().value() as u64;
+        while elapsed < ticks {
+            // the counter counts down and wraps to the reload value
+            let current = self.0.cvr.read().value() as u64;
+            elapsed += if current <= previous {
+                previous - current
+            } else {
+                previous + ticks_per_ms - current
+            };
+            previous = current;
+        }
+    }
 }
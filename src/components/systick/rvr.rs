@@ -4,6 +4,10 @@
 pub struct Register(u32);
 
 impl Register {
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
     pub fn set(&mut self, value: u32) {
         assert!(value & 0xff00_0000 == 0);
         self.0 = value;
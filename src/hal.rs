@@ -0,0 +1,119 @@
+//! Implementations of the `embedded-hal` traits (enabled by the `embedded-hal` feature)
+//!
+//! This allows drivers that are written against `embedded-hal` to run on top of the GPIO
+//! interface and the SysTick timer.
+
+use core::convert::Infallible;
+use embedded_hal::digital::v2 as digital;
+use embedded_hal::blocking::delay::{DelayMs, DelayUs};
+use components::systick::SysTick;
+use interfaces::gpio::{InputPin, OutputPin, IoPin};
+
+impl digital::InputPin for InputPin {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Infallible> {
+        Ok(self.get())
+    }
+
+    fn is_low(&self) -> Result<bool, Infallible> {
+        Ok(!self.get())
+    }
+}
+
+impl digital::OutputPin for OutputPin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.set(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.set(true);
+        Ok(())
+    }
+}
+
+impl digital::StatefulOutputPin for OutputPin {
+    fn is_set_high(&self) -> Result<bool, Infallible> {
+        Ok(self.get())
+    }
+
+    fn is_set_low(&self) -> Result<bool, Infallible> {
+        Ok(!self.get())
+    }
+}
+
+impl digital::ToggleableOutputPin for OutputPin {
+    type Error = Infallible;
+
+    fn toggle(&mut self) -> Result<(), Infallible> {
+        OutputPin::toggle(self);
+        Ok(())
+    }
+}
+
+/// Reads the actual line level.
+impl digital::InputPin for IoPin {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Infallible> {
+        Ok(self.get())
+    }
+
+    fn is_low(&self) -> Result<bool, Infallible> {
+        Ok(!self.get())
+    }
+}
+
+/// `set_high` releases the line, `set_low` drives it low.
+impl digital::OutputPin for IoPin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.set(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.set(true);
+        Ok(())
+    }
+}
+
+impl DelayMs<u32> for SysTick {
+    fn delay_ms(&mut self, ms: u32) {
+        self.busy_wait(ms);
+    }
+}
+
+impl DelayMs<u16> for SysTick {
+    fn delay_ms(&mut self, ms: u16) {
+        self.busy_wait(ms as u32);
+    }
+}
+
+impl DelayMs<u8> for SysTick {
+    fn delay_ms(&mut self, ms: u8) {
+        self.busy_wait(ms as u32);
+    }
+}
+
+impl DelayUs<u32> for SysTick {
+    fn delay_us(&mut self, us: u32) {
+        self.busy_wait_us(us);
+    }
+}
+
+impl DelayUs<u16> for SysTick {
+    fn delay_us(&mut self, us: u16) {
+        self.busy_wait_us(us as u32);
+    }
+}
+
+impl DelayUs<u8> for SysTick {
+    fn delay_us(&mut self, us: u8) {
+        self.busy_wait_us(us as u32);
+    }
+}
@@ -0,0 +1,141 @@
+//! External interrupts of input pins
+//!
+//! Each EXTI line `n` can be connected to pin `n` of exactly one port. `Exti` keeps track of the
+//! claimed lines, so that two ports can't claim the same line number. The SYSCFG clock needs to
+//! be enabled (`apb2_enr::SYSCFG_ENABLE`) before lines are configured.
+
+use core::{mem, ptr};
+use components::syscfg::SyscfgBank;
+use components::exti::ExtiBank;
+use components::exti::lines;
+use super::{Error, InputPin};
+use irq;
+
+/// Lines that are claimed by an `ExtiLine` handle.
+///
+/// This lives in a static so that dropped handles can give their line back.
+static mut LINE_IN_USE: [bool; 16] = [false; 16];
+
+/// Signal edge that triggers an interrupt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Rising,
+    Falling,
+    Both,
+}
+
+/// Owner of the EXTI line multiplexing and the EXTI controller.
+///
+/// The controller is kept as a raw pointer, since every `ExtiLine` accesses it as well.
+pub struct Exti {
+    syscfg: &'static mut SyscfgBank,
+    exti: *mut ExtiBank,
+}
+
+impl Exti {
+    pub fn new(syscfg: &'static mut SyscfgBank, exti: &'static mut ExtiBank) -> Exti {
+        Exti {
+            syscfg: syscfg,
+            exti: exti,
+        }
+    }
+}
+
+impl InputPin {
+    /// Connects the pin to its EXTI line and enables the interrupt for `edge`.
+    ///
+    /// The returned handle owns the pin until `ExtiLine::disable` gives it back, so the line
+    /// can't stay armed for a pin that was dropped or reconfigured. Fails if the line with the
+    /// number of the pin is already claimed by another port; the pin is then given back to its
+    /// port.
+    pub fn enable_interrupt(self, exti: &mut Exti, edge: Edge) -> Result<ExtiLine, Error> {
+        let line = self.pin as u8;
+        let port = self.port.port;
+        let bank = exti.exti;
+        let syscfg = &mut *exti.syscfg;
+        // an ExtiLine can be dropped in an interrupt handler, which updates IMR as well
+        let claimed = irq::free(|| unsafe {
+            if LINE_IN_USE[line as usize] {
+                return false;
+            }
+            LINE_IN_USE[line as usize] = true;
+
+            syscfg.exticr[(line / 4) as usize].update(|r| r.set_port(line, port as u32));
+            (*bank).rtsr.update(|r| r.set(line, edge != Edge::Falling));
+            (*bank).ftsr.update(|r| r.set(line, edge != Edge::Rising));
+
+            // the pending bits are cleared by writing 1, so only set our own bit
+            let mut pr = lines::Register::default();
+            pr.set(line, true);
+            (*bank).pr.write(pr);
+            (*bank).imr.update(|r| r.set(line, true));
+            true
+        });
+        if !claimed {
+            return Err(Error::ExtiLineInUse((port, self.pin)));
+        }
+
+        let line = ExtiLine {
+            pin: self,
+            exti: bank,
+        };
+        Ok(line)
+    }
+}
+
+/// An enabled EXTI line and the input pin connected to it.
+///
+/// Dropping the handle masks the interrupt and gives the line and the pin back.
+pub struct ExtiLine {
+    pin: InputPin,
+    exti: *mut ExtiBank,
+}
+
+unsafe impl Send for ExtiLine {}
+
+impl ExtiLine {
+    /// Returns the number of the line, which is also the number of the pin.
+    pub fn line(&self) -> u8 {
+        self.pin.pin as u8
+    }
+
+    /// Returns the input pin, e.g. to read its level in the interrupt handler.
+    pub fn pin(&self) -> &InputPin {
+        &self.pin
+    }
+
+    /// Masks the interrupt, gives the line back and returns the pin.
+    pub fn disable(self) -> InputPin {
+        self.mask_and_free();
+        let pin = unsafe { ptr::read(&self.pin) };
+        mem::forget(self);
+        pin
+    }
+
+    fn mask_and_free(&self) {
+        let line = self.line();
+        irq::free(|| unsafe {
+            (*self.exti).imr.update(|r| r.set(line, false));
+            LINE_IN_USE[line as usize] = false;
+        });
+    }
+
+    /// Returns whether the selected edge occurred since the pending bit was last cleared.
+    pub fn is_pending(&self) -> bool {
+        unsafe { (*self.exti).pr.read().get(self.line()) }
+    }
+
+    /// Clears the pending bit, which needs to be done in the interrupt handler.
+    pub fn clear_pending(&self) {
+        // the pending bits are cleared by writing 1, so only set our own bit
+        let mut pr = lines::Register::default();
+        pr.set(self.line(), true);
+        unsafe { (*self.exti).pr.write(pr) };
+    }
+}
+
+impl Drop for ExtiLine {
+    fn drop(&mut self) {
+        self.mask_and_free();
+    }
+}
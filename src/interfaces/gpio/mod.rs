@@ -1,53 +1,97 @@
 use components::gpio::stm32f7;
-use volatile::{ReadOnly, WriteOnly, ReadWrite};
 use arrayvec::ArrayVec;
+use core::{fmt, mem};
+use core::marker::PhantomData;
+use peripherals::GpioBanks;
+use irq;
 
-pub use components::gpio::stm32f7::{Pin, OutputType, OutputSpeed, Resistor, AlternateFunction};
+pub use components::gpio::stm32f7::{Pin, Mode, OutputType, OutputSpeed, Resistor,
+                                    AlternateFunction};
+
+pub use self::signal::{Signal, SignalType, signals};
+pub use self::exti::{Exti, ExtiLine, Edge};
+
+pub mod typestate;
+mod signal;
+mod exti;
 
 #[derive(Debug)]
 pub enum Error {
-    PinAlreadyInUse(Pin),
+    /// The pin is already owned by a handle or its configuration is locked.
+    PinAlreadyInUse((Port, Pin), PinOwner),
+    /// The lock key write sequence did not lock all requested pins of the port.
+    LockFailed(Port),
+    /// The pin has no alternate function that carries the signal.
+    InvalidSignal((Port, Pin), Signal),
+    /// The EXTI line with the number of the pin is already claimed by another port.
+    ExtiLineInUse((Port, Pin)),
+    /// The pin is listed more than once.
+    DuplicatePin((Port, Pin)),
+}
+
+/// What a pin is currently used for, as reported by `Error::PinAlreadyInUse`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinOwner {
+    Input,
+    Output,
+    Analog,
+    AlternateFunction(AlternateFunction),
+    /// The configuration of the pin is locked until the next reset.
+    Locked,
 }
 
+/// Ownership bookkeeping of all ports, indexed by `Port` and `Pin`.
+///
+/// This lives in a static instead of the `GpioPort` so that pin handles can give their pin back
+/// when they are dropped, even if the `GpioPort` was moved in the meantime. Handles can be dropped
+/// in interrupt handlers, so all accesses happen in `irq::free` critical sections.
+static mut PIN_IN_USE: [[bool; 16]; 11] = [[false; 16]; 11];
+
+/// The pins of a port in ascending order
+static ALL_PINS: [Pin; 16] = [Pin::Pin0, Pin::Pin1, Pin::Pin2, Pin::Pin3, Pin::Pin4, Pin::Pin5,
+                              Pin::Pin6, Pin::Pin7, Pin::Pin8, Pin::Pin9, Pin::Pin10, Pin::Pin11,
+                              Pin::Pin12, Pin::Pin13, Pin::Pin14, Pin::Pin15];
+
 pub struct Gpio {
     port_a: GpioPort,
     port_b: GpioPort,
     port_c: GpioPort,
     port_d: GpioPort,
+    #[cfg(has_gpio_e)]
     port_e: GpioPort,
+    #[cfg(has_gpio_f)]
     port_f: GpioPort,
+    #[cfg(has_gpio_g)]
     port_g: GpioPort,
     port_h: GpioPort,
+    #[cfg(has_gpio_i)]
     port_i: GpioPort,
+    #[cfg(has_gpio_j)]
     port_j: GpioPort,
+    #[cfg(has_gpio_k)]
     port_k: GpioPort,
 }
 
 impl Gpio {
-    pub fn new(gpio_a: &'static mut stm32f7::Gpio,
-               gpio_b: &'static mut stm32f7::Gpio,
-               gpio_c: &'static mut stm32f7::Gpio,
-               gpio_d: &'static mut stm32f7::Gpio,
-               gpio_e: &'static mut stm32f7::Gpio,
-               gpio_f: &'static mut stm32f7::Gpio,
-               gpio_g: &'static mut stm32f7::Gpio,
-               gpio_h: &'static mut stm32f7::Gpio,
-               gpio_i: &'static mut stm32f7::Gpio,
-               gpio_j: &'static mut stm32f7::Gpio,
-               gpio_k: &'static mut stm32f7::Gpio)
-               -> Gpio {
+    pub fn new(banks: GpioBanks) -> Gpio {
         Gpio {
-            port_a: GpioPort::new(gpio_a),
-            port_b: GpioPort::new(gpio_b),
-            port_c: GpioPort::new(gpio_c),
-            port_d: GpioPort::new(gpio_d),
-            port_e: GpioPort::new(gpio_e),
-            port_f: GpioPort::new(gpio_f),
-            port_g: GpioPort::new(gpio_g),
-            port_h: GpioPort::new(gpio_h),
-            port_i: GpioPort::new(gpio_i),
-            port_j: GpioPort::new(gpio_j),
-            port_k: GpioPort::new(gpio_k),
+            port_a: GpioPort::new(Port::PortA, banks.gpio_a),
+            port_b: GpioPort::new(Port::PortB, banks.gpio_b),
+            port_c: GpioPort::new(Port::PortC, banks.gpio_c),
+            port_d: GpioPort::new(Port::PortD, banks.gpio_d),
+            #[cfg(has_gpio_e)]
+            port_e: GpioPort::new(Port::PortE, banks.gpio_e),
+            #[cfg(has_gpio_f)]
+            port_f: GpioPort::new(Port::PortF, banks.gpio_f),
+            #[cfg(has_gpio_g)]
+            port_g: GpioPort::new(Port::PortG, banks.gpio_g),
+            port_h: GpioPort::new(Port::PortH, banks.gpio_h),
+            #[cfg(has_gpio_i)]
+            port_i: GpioPort::new(Port::PortI, banks.gpio_i),
+            #[cfg(has_gpio_j)]
+            port_j: GpioPort::new(Port::PortJ, banks.gpio_j),
+            #[cfg(has_gpio_k)]
+            port_k: GpioPort::new(Port::PortK, banks.gpio_k),
         }
     }
 
@@ -64,16 +108,75 @@ impl Gpio {
         self.port(pin.0).to_output(pin.1, out_type, out_speed, resistor)
     }
 
+    pub fn to_io(&mut self,
+                 pin: (Port, Pin),
+                 out_speed: OutputSpeed,
+                 resistor: Resistor)
+                 -> Result<IoPin, Error> {
+        self.port(pin.0).to_io(pin.1, out_speed, resistor)
+    }
+
+    pub fn to_analog(&mut self, pin: (Port, Pin)) -> Result<AnalogPin, Error> {
+        self.port(pin.0).to_analog(pin.1)
+    }
+
+    pub fn to_input_group(&mut self,
+                          port: Port,
+                          pins: &[Pin],
+                          resistor: Resistor)
+                          -> Result<InputGroup, Error> {
+        self.port(port).to_input_group(pins, resistor)
+    }
+
+    pub fn to_output_group(&mut self,
+                           port: Port,
+                           pins: &[Pin],
+                           out_type: OutputType,
+                           out_speed: OutputSpeed,
+                           resistor: Resistor)
+                           -> Result<OutputGroup, Error> {
+        self.port(port).to_output_group(pins, out_type, out_speed, resistor)
+    }
+
     pub fn to_alternate_function(&mut self,
                                  pin: (Port, Pin),
                                  alternate_fn: AlternateFunction,
                                  typ: OutputType,
                                  speed: OutputSpeed,
                                  resistor: Resistor)
-                                 -> Result<(), Error> {
+                                 -> Result<AlternateFunctionPin, Error> {
         self.port(pin.0).to_alternate_function(pin.1, alternate_fn, typ, speed, resistor)
     }
 
+    /// Routes the signal `S` to `pin` after checking that the pin supports it.
+    ///
+    /// The returned handle is what peripheral drivers expect as proof that the signal is
+    /// available on a pin:
+    ///
+    /// ```ignore
+    /// let tx = gpio.to_alternate_function_for::<signals::Usart1Tx>((PortA, Pin9), ...)?;
+    /// ```
+    pub fn to_alternate_function_for<S>(&mut self,
+                                        pin: (Port, Pin),
+                                        typ: OutputType,
+                                        speed: OutputSpeed,
+                                        resistor: Resistor)
+                                        -> Result<SignalPin<S>, Error>
+        where S: SignalType
+    {
+        let signal = S::signal();
+        let alternate_fn = match signal::alternate_function(signal, pin) {
+            Some(alternate_fn) => alternate_fn,
+            None => return Err(Error::InvalidSignal(pin, signal)),
+        };
+        let pin = self.to_alternate_function(pin, alternate_fn, typ, speed, resistor)?;
+
+        Ok(SignalPin {
+            pin: pin,
+            _signal: PhantomData,
+        })
+    }
+
     pub fn to_alternate_function_all(&mut self,
                                      pins: &[(Port, Pin)],
                                      alternate_fn: AlternateFunction,
@@ -81,31 +184,16 @@ impl Gpio {
                                      speed: OutputSpeed,
                                      resistor: Resistor)
                                      -> Result<(), Error> {
+        irq::free(|| self.use_pins_of_all_ports(pins))?;
 
-        // check that all pins are unused
-        let mut pin_in_use = [self.port_a.pin_in_use,
-                              self.port_b.pin_in_use,
-                              self.port_c.pin_in_use,
-                              self.port_d.pin_in_use,
-                              self.port_e.pin_in_use,
-                              self.port_f.pin_in_use,
-                              self.port_g.pin_in_use,
-                              self.port_h.pin_in_use,
-                              self.port_i.pin_in_use,
-                              self.port_j.pin_in_use,
-                              self.port_k.pin_in_use];
-        for &(port, pin) in pins {
-            if pin_in_use[port as usize][pin as usize] {
-                return Err(Error::PinAlreadyInUse(pin));
-            } else {
-                pin_in_use[port as usize][pin as usize] = true;
+        // configure the pins for each port
+        let mut port_done = [false; 11];
+        for &(port, _) in pins {
+            if port_done[port as usize] {
+                continue;
             }
-        }
+            port_done[port as usize] = true;
 
-        // configure the pins for each port
-        use self::Port::*;
-        let ports = [PortA, PortB, PortC, PortD, PortE, PortF, PortG, PortH, PortI, PortJ, PortK];
-        for &port in ports.iter() {
             // create a pin_vec that contains all pins belonging to the port
             let mut pin_vec = ArrayVec::<[_; 16]>::new();
             for pin in pins.iter().filter(|p| p.0 == port).map(|p| p.1) {
@@ -114,12 +202,76 @@ impl Gpio {
             }
 
             // configure the pins as alternate function pins
-            self.port(port)
-                .to_alternate_function_all(pin_vec.as_slice(), alternate_fn, typ, speed, resistor)?;
+            self.port(port).configure_alternate_function(pin_vec.as_slice(),
+                                                         alternate_fn,
+                                                         typ,
+                                                         speed,
+                                                         resistor);
         }
         Ok(())
     }
 
+    /// Marks all `pins` as used, or none of them if one is unavailable.
+    fn use_pins_of_all_ports(&mut self, pins: &[(Port, Pin)]) -> Result<(), Error> {
+        // work on a copy of the bookkeeping, which is only written back on success
+        let mut pin_in_use = unsafe { PIN_IN_USE };
+        for &(port, pin) in pins {
+            if let Some(owner) = self.port(port).port.owner(pin) {
+                return Err(Error::PinAlreadyInUse((port, pin), owner));
+            }
+            if pin_in_use[port as usize][pin as usize] {
+                return Err(Error::DuplicatePin((port, pin)));
+            } else {
+                pin_in_use[port as usize][pin as usize] = true;
+            }
+        }
+        unsafe { PIN_IN_USE = pin_in_use };
+        Ok(())
+    }
+
+    /// Marks the pins of all ports as used, or fails if a handle still owns one of them.
+    fn use_all_pins(&mut self) -> Result<(), Error> {
+        irq::free(|| {
+            for port in self.ports() {
+                for &pin in ALL_PINS.iter() {
+                    if unsafe { PIN_IN_USE[port.port.port as usize][pin as usize] } {
+                        let owner = port.owner(pin).expect("pin in use has an owner");
+                        return Err(Error::PinAlreadyInUse((port.port.port, pin), owner));
+                    }
+                }
+            }
+            unsafe { PIN_IN_USE = [[true; 16]; 11] };
+            Ok(())
+        })
+    }
+
+    /// Returns all ports that the selected chip variant and package have.
+    fn ports(&mut self) -> ArrayVec<[&mut GpioPort; 11]> {
+        let mut ports = ArrayVec::new();
+        ports.push(&mut self.port_a);
+        ports.push(&mut self.port_b);
+        ports.push(&mut self.port_c);
+        ports.push(&mut self.port_d);
+        #[cfg(has_gpio_e)]
+        ports.push(&mut self.port_e);
+        #[cfg(has_gpio_f)]
+        ports.push(&mut self.port_f);
+        #[cfg(has_gpio_g)]
+        ports.push(&mut self.port_g);
+        ports.push(&mut self.port_h);
+        #[cfg(has_gpio_i)]
+        ports.push(&mut self.port_i);
+        #[cfg(has_gpio_j)]
+        ports.push(&mut self.port_j);
+        #[cfg(has_gpio_k)]
+        ports.push(&mut self.port_k);
+        ports
+    }
+
+    /// Returns the port.
+    ///
+    /// Only the ports of the selected chip variant and package exist, so using another port fails
+    /// to compile.
     pub fn port(&mut self, port: Port) -> &mut GpioPort {
         use self::Port::*;
         match port {
@@ -127,69 +279,73 @@ impl Gpio {
             PortB => &mut self.port_b,
             PortC => &mut self.port_c,
             PortD => &mut self.port_d,
+            #[cfg(has_gpio_e)]
             PortE => &mut self.port_e,
+            #[cfg(has_gpio_f)]
             PortF => &mut self.port_f,
+            #[cfg(has_gpio_g)]
             PortG => &mut self.port_g,
             PortH => &mut self.port_h,
+            #[cfg(has_gpio_i)]
             PortI => &mut self.port_i,
+            #[cfg(has_gpio_j)]
             PortJ => &mut self.port_j,
+            #[cfg(has_gpio_k)]
             PortK => &mut self.port_k,
         }
     }
 }
 
+/// GPIO port
+///
+/// Only the ports of the selected chip variant and package exist.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Port {
     PortA = 0,
-    PortB,
-    PortC,
-    PortD,
-    PortE,
-    PortF,
-    PortG,
-    PortH,
-    PortI,
-    PortJ,
-    PortK,
+    PortB = 1,
+    PortC = 2,
+    PortD = 3,
+    #[cfg(has_gpio_e)]
+    PortE = 4,
+    #[cfg(has_gpio_f)]
+    PortF = 5,
+    #[cfg(has_gpio_g)]
+    PortG = 6,
+    PortH = 7,
+    #[cfg(has_gpio_i)]
+    PortI = 8,
+    #[cfg(has_gpio_j)]
+    PortJ = 9,
+    #[cfg(has_gpio_k)]
+    PortK = 10,
 }
 
 pub struct GpioPort {
-    pin_in_use: [bool; 16],
-    mode: &'static mut ReadWrite<stm32f7::ModeRegister>,
-    out_type: &'static mut ReadWrite<stm32f7::OutputTypeRegister>,
-    out_speed: &'static mut ReadWrite<stm32f7::OutputSpeedRegister>,
-    pupd: &'static mut ReadWrite<stm32f7::ResistorRegister>,
-    input_data: &'static ReadOnly<stm32f7::InputDataRegister>,
-    output_data: &'static ReadOnly<stm32f7::OutputDataRegister>,
-    bit_set_reset: BsrrRef,
-    alternate_fn: &'static mut ReadWrite<stm32f7::AlternateFunctionRegister>,
+    port: PortRef,
 }
 
 impl GpioPort {
-    pub fn new(gpio: &'static mut stm32f7::Gpio) -> GpioPort {
+    pub fn new(port: Port, gpio: &'static mut stm32f7::Gpio) -> GpioPort {
         GpioPort {
-            pin_in_use: [false; 16],
-            mode: &mut gpio.mode,
-            out_type: &mut gpio.out_type,
-            out_speed: &mut gpio.out_speed,
-            pupd: &mut gpio.pupd,
-            input_data: &gpio.input_data,
-            output_data: &gpio.output_data,
-            bit_set_reset: BsrrRef(&mut gpio.bit_set_reset),
-            alternate_fn: &mut gpio.alternate_fn,
+            port: PortRef {
+                port: port,
+                registers: gpio,
+            },
         }
     }
 
     pub fn to_input(&mut self, pin: Pin, resistor: Resistor) -> Result<InputPin, Error> {
         self.use_pin(pin)?;
 
-        self.mode.update(|r| r.set(pin, stm32f7::Mode::Input));
-        self.pupd.update(|r| r.set(pin, resistor));
+        self.port.update(|gpio| {
+            gpio.mode.update(|r| r.set(pin, stm32f7::Mode::Input));
+            gpio.pupd.update(|r| r.set(pin, resistor));
+        });
 
         Ok(InputPin {
             pin: pin,
-            input_data: self.input_data,
+            port: self.port.clone(),
         })
     }
 
@@ -201,15 +357,114 @@ impl GpioPort {
                      -> Result<OutputPin, Error> {
         self.use_pin(pin)?;
 
-        self.mode.update(|r| r.set(pin, stm32f7::Mode::Output));
-        self.out_type.update(|r| r.set(pin, out_type));
-        self.out_speed.update(|r| r.set(pin, out_speed));
-        self.pupd.update(|r| r.set(pin, resistor));
+        self.port.update(|gpio| {
+            gpio.mode.update(|r| r.set(pin, stm32f7::Mode::Output));
+            gpio.out_type.update(|r| r.set(pin, out_type));
+            gpio.out_speed.update(|r| r.set(pin, out_speed));
+            gpio.pupd.update(|r| r.set(pin, resistor));
+        });
 
         Ok(OutputPin {
             pin: pin,
-            output_data: self.output_data,
-            bit_set_reset: self.bit_set_reset.clone(),
+            port: self.port.clone(),
+        })
+    }
+
+    /// Configures `pin` as bidirectional open-drain pin, e.g. for I2C or 1-Wire lines.
+    ///
+    /// The line is released before the pin is switched to output mode.
+    pub fn to_io(&mut self,
+                 pin: Pin,
+                 out_speed: OutputSpeed,
+                 resistor: Resistor)
+                 -> Result<IoPin, Error> {
+        self.use_pin(pin)?;
+
+        self.port.set_output(pin, true);
+        self.port.update(|gpio| {
+            gpio.out_type.update(|r| r.set(pin, OutputType::OpenDrain));
+            gpio.out_speed.update(|r| r.set(pin, out_speed));
+            gpio.pupd.update(|r| r.set(pin, resistor));
+            gpio.mode.update(|r| r.set(pin, stm32f7::Mode::Output));
+        });
+
+        Ok(IoPin {
+            pin: pin,
+            port: self.port.clone(),
+        })
+    }
+
+    /// Configures `pin` for analog use and disables its pull resistors.
+    ///
+    /// Analog mode is also the mode with the lowest power consumption, so unused pins can be
+    /// parked with this method.
+    pub fn to_analog(&mut self, pin: Pin) -> Result<AnalogPin, Error> {
+        self.use_pin(pin)?;
+
+        self.port.update(|gpio| {
+            gpio.mode.update(|r| r.set(pin, stm32f7::Mode::Analog));
+            gpio.pupd.update(|r| r.set(pin, Resistor::NoPull));
+        });
+
+        Ok(AnalogPin {
+            pin: pin,
+            port: self.port.clone(),
+        })
+    }
+
+    /// Configures `pins` as inputs that are read together with a single register read.
+    ///
+    /// Bit `i` of the values read from the group corresponds to `pins[i]`.
+    pub fn to_input_group(&mut self,
+                          pins: &[Pin],
+                          resistor: Resistor)
+                          -> Result<InputGroup, Error> {
+        self.use_pins(pins)?;
+
+        self.port.update(|gpio| {
+            gpio.mode.update(|r| for &pin in pins {
+                r.set(pin, stm32f7::Mode::Input)
+            });
+            gpio.pupd.update(|r| for &pin in pins {
+                r.set(pin, resistor)
+            });
+        });
+
+        Ok(InputGroup {
+            pins: pins.iter().cloned().collect(),
+            port: self.port.clone(),
+        })
+    }
+
+    /// Configures `pins` as outputs that are written together with a single register write.
+    ///
+    /// Bit `i` of the values written to the group corresponds to `pins[i]`.
+    pub fn to_output_group(&mut self,
+                           pins: &[Pin],
+                           out_type: OutputType,
+                           out_speed: OutputSpeed,
+                           resistor: Resistor)
+                           -> Result<OutputGroup, Error> {
+        self.use_pins(pins)?;
+
+        self.port.update(|gpio| {
+            gpio.mode.update(|r| for &pin in pins {
+                r.set(pin, stm32f7::Mode::Output)
+            });
+            gpio.out_type.update(|r| for &pin in pins {
+                r.set(pin, out_type)
+            });
+            gpio.out_speed.update(|r| for &pin in pins {
+                r.set(pin, out_speed)
+            });
+            gpio.pupd.update(|r| for &pin in pins {
+                r.set(pin, resistor)
+            });
+        });
+
+        Ok(OutputGroup {
+            pins: pins.iter().cloned().collect(),
+            port: self.port.clone(),
         })
     }
 
@@ -219,10 +474,19 @@ impl GpioPort {
                                  typ: OutputType,
                                  speed: OutputSpeed,
                                  resistor: Resistor)
-                                 -> Result<(), Error> {
-        self.to_alternate_function_all(&[pin], alternate_fn, typ, speed, resistor)
+                                 -> Result<AlternateFunctionPin, Error> {
+        self.to_alternate_function_all(&[pin], alternate_fn, typ, speed, resistor)?;
+
+        Ok(AlternateFunctionPin {
+            pin: pin,
+            port: self.port.clone(),
+        })
     }
 
+    /// Configures the given pins for the alternate function.
+    ///
+    /// In contrast to `to_alternate_function`, no handles are created, so the pins stay in use
+    /// for the rest of the program.
     pub fn to_alternate_function_all(&mut self,
                                      pins: &[Pin],
                                      alternate_fn: AlternateFunction,
@@ -231,94 +495,691 @@ impl GpioPort {
                                      resistor: Resistor)
                                      -> Result<(), Error> {
         self.use_pins(pins)?;
+        self.configure_alternate_function(pins, alternate_fn, typ, speed, resistor);
+        Ok(())
+    }
 
-        self.mode.update(|r| for &pin in pins {
-            r.set(pin, stm32f7::Mode::AlternateFunction)
-        });
-        self.pupd.update(|r| for &pin in pins {
-            r.set(pin, resistor)
-        });
-        self.out_type.update(|r| for &pin in pins {
-            r.set(pin, typ)
-        });
-        self.out_speed.update(|r| for &pin in pins {
-            r.set(pin, speed)
-        });
-        self.alternate_fn.update(|r| for &pin in pins {
-            r.set(pin, alternate_fn)
+    fn configure_alternate_function(&mut self,
+                                    pins: &[Pin],
+                                    alternate_fn: AlternateFunction,
+                                    typ: OutputType,
+                                    speed: OutputSpeed,
+                                    resistor: Resistor) {
+        self.port.update(|gpio| {
+            gpio.mode.update(|r| for &pin in pins {
+                r.set(pin, stm32f7::Mode::AlternateFunction)
+            });
+            gpio.pupd.update(|r| for &pin in pins {
+                r.set(pin, resistor)
+            });
+            gpio.out_type.update(|r| for &pin in pins {
+                r.set(pin, typ)
+            });
+            gpio.out_speed.update(|r| for &pin in pins {
+                r.set(pin, speed)
+            });
+            gpio.alternate_fn.update(|r| for &pin in pins {
+                r.set(pin, alternate_fn)
+            });
         });
+    }
 
-        Ok(())
+    /// Locks the configuration of `pins` until the next reset.
+    ///
+    /// Runs the lock key write sequence and checks that it took effect. Since the sequence
+    /// freezes the whole lock register, it can only run once per port; later calls only succeed
+    /// if all `pins` are already locked. Locked pins are refused by all methods that would
+    /// reconfigure them.
+    pub fn lock_pins(&mut self, pins: &[Pin]) -> Result<(), Error> {
+        if !self.port.read(|gpio| gpio.lckr.read().lock_key()) {
+            let mut lckr = stm32f7::LockRegister::default();
+            for &pin in pins {
+                lckr.set_locked(pin, true);
+            }
+
+            // lock key write sequence: write LCKK=1, write LCKK=0, write LCKK=1, read, read
+            let locked = self.port.update(|gpio| {
+                lckr.set_lock_key(true);
+                gpio.lckr.write(lckr);
+                lckr.set_lock_key(false);
+                gpio.lckr.write(lckr);
+                lckr.set_lock_key(true);
+                gpio.lckr.write(lckr);
+                gpio.lckr.read();
+                gpio.lckr.read().lock_key()
+            });
+            if !locked {
+                return Err(Error::LockFailed(self.port.port));
+            }
+        }
+
+        if pins.iter().all(|&pin| self.is_locked(pin)) {
+            Ok(())
+        } else {
+            Err(Error::LockFailed(self.port.port))
+        }
+    }
+
+    /// Returns the current configuration of `pin`, decoded from the port registers.
+    pub fn pin_config(&self, pin: Pin) -> PinConfig {
+        self.port.read(|gpio| {
+            PinConfig {
+                mode: gpio.mode.read().get(pin),
+                out_type: gpio.out_type.read().get(pin),
+                out_speed: gpio.out_speed.read().get(pin),
+                resistor: gpio.pupd.read().get(pin),
+                alternate_fn: gpio.alternate_fn.read().get(pin),
+            }
+        })
+    }
+
+    /// Returns whether the configuration of `pin` is locked.
+    pub fn is_locked(&self, pin: Pin) -> bool {
+        self.port.is_locked(pin)
+    }
+
+    /// Returns what `pin` is currently used for, or `None` if it is free.
+    pub fn owner(&self, pin: Pin) -> Option<PinOwner> {
+        self.port.owner(pin)
     }
 
     fn use_pin(&mut self, pin: Pin) -> Result<(), Error> {
-        if self.pin_in_use[pin as usize] {
-            Err(Error::PinAlreadyInUse(pin))
-        } else {
-            self.pin_in_use[pin as usize] = true;
+        let port = &self.port;
+        irq::free(|| {
+            if let Some(owner) = port.owner(pin) {
+                return Err(Error::PinAlreadyInUse((port.port, pin), owner));
+            }
+            unsafe { PIN_IN_USE[port.port as usize][pin as usize] = true };
             Ok(())
-        }
+        })
     }
 
+    /// Marks all `pins` as used, or none of them if one is unavailable.
     fn use_pins(&mut self, pins: &[Pin]) -> Result<(), Error> {
-        // create a copy of the pin_in_use array since we only want to modify it in case of success
-        let mut pin_in_use = self.pin_in_use;
+        let port = &self.port;
+        irq::free(|| {
+            // create a copy of the pin_in_use array since we only want to modify it in case of
+            // success
+            let mut pin_in_use = unsafe { PIN_IN_USE[port.port as usize] };
 
-        for &pin in pins {
-            if pin_in_use[pin as usize] {
-                return Err(Error::PinAlreadyInUse(pin));
-            } else {
-                pin_in_use[pin as usize] = true;
+            for &pin in pins {
+                if let Some(owner) = port.owner(pin) {
+                    return Err(Error::PinAlreadyInUse((port.port, pin), owner));
+                }
+                if pin_in_use[pin as usize] {
+                    return Err(Error::DuplicatePin((port.port, pin)));
+                } else {
+                    pin_in_use[pin as usize] = true;
+                }
             }
-        }
 
-        // success => write back updated pin_in_use array
-        self.pin_in_use = pin_in_use;
+            // success => write back updated pin_in_use array
+            unsafe { PIN_IN_USE[port.port as usize] = pin_in_use };
 
+            Ok(())
+        })
+    }
+}
+
+/// Configuration of a single pin, as returned by `GpioPort::pin_config`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinConfig {
+    pub mode: Mode,
+    pub out_type: OutputType,
+    pub out_speed: OutputSpeed,
+    /// `None` if the pull-up/pull-down register contains the reserved value
+    pub resistor: Option<Resistor>,
+    pub alternate_fn: AlternateFunction,
+}
+
+/// Prints the configuration and state of all pins of the port as a table.
+impl fmt::Display for GpioPort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let input_data = self.port.input_data();
+        let output_data = self.port.output_data();
+
+        writeln!(f, "{:?}", self.port.port)?;
+        writeln!(f, "pin  mode    type        speed      pull       af    in  out  owner")?;
+        for &pin in ALL_PINS.iter() {
+            let config = self.pin_config(pin);
+            let mode = match config.mode {
+                Mode::Input => "input",
+                Mode::Output => "output",
+                Mode::AlternateFunction => "alt fn",
+                Mode::Analog => "analog",
+            };
+            let out_type = match config.out_type {
+                OutputType::PushPull => "push-pull",
+                OutputType::OpenDrain => "open-drain",
+            };
+            let out_speed = match config.out_speed {
+                OutputSpeed::Low => "low",
+                OutputSpeed::Medium => "medium",
+                OutputSpeed::High => "high",
+                OutputSpeed::VeryHigh => "very high",
+            };
+            let resistor = match config.resistor {
+                Some(Resistor::NoPull) => "none",
+                Some(Resistor::PullUp) => "pull-up",
+                Some(Resistor::PullDown) => "pull-down",
+                None => "reserved",
+            };
+            let owner = match self.owner(pin) {
+                Some(PinOwner::Locked) => "locked",
+                Some(_) => "in use",
+                None => "free",
+            };
+            writeln!(f,
+                     "{:>3}  {:<6}  {:<10}  {:<9}  {:<9}  AF{:<2}  {:>2}  {:>3}  {}",
+                     pin as u8,
+                     mode,
+                     out_type,
+                     out_speed,
+                     resistor,
+                     config.alternate_fn.number(),
+                     input_data.get(pin) as u8,
+                     output_data.get(pin) as u8,
+                     owner)?;
+        }
         Ok(())
     }
 }
 
+/// A pin configured as input.
+///
+/// Dropping the handle gives the pin back to its port without touching its configuration.
 pub struct InputPin {
     pin: Pin,
-    input_data: &'static ReadOnly<stm32f7::InputDataRegister>,
+    port: PortRef,
 }
 
 impl InputPin {
     pub fn get(&self) -> bool {
-        self.input_data.read().get(self.pin)
+        self.port.input_data().get(self.pin)
+    }
+
+    /// Resets the pin mode to its reset state and gives the pin back to its port.
+    pub fn release(self) {
+        self.port.reset_mode(self.pin);
+    }
+}
+
+impl Drop for InputPin {
+    fn drop(&mut self) {
+        self.port.free(self.pin);
     }
 }
 
+/// A pin configured as output.
+///
+/// Dropping the handle gives the pin back to its port without touching its configuration, so
+/// the pin keeps driving its last value.
 pub struct OutputPin {
     pin: Pin,
-    output_data: &'static ReadOnly<stm32f7::OutputDataRegister>,
-    bit_set_reset: BsrrRef,
+    port: PortRef,
 }
 
 impl OutputPin {
     pub fn get(&self) -> bool {
-        self.output_data.read().get(self.pin)
+        self.port.output_data().get(self.pin)
     }
 
     pub fn set(&mut self, value: bool) {
-        self.bit_set_reset.set(self.pin, value);
+        self.port.set_output(self.pin, value);
+    }
+
+    /// Inverts the output value.
+    ///
+    /// The new value is written through the bit set/reset register, so other pins of the port
+    /// are never affected.
+    pub fn toggle(&mut self) {
+        let value = self.get();
+        self.set(!value);
+    }
+
+    /// Resets the pin mode to its reset state and gives the pin back to its port.
+    pub fn release(self) {
+        self.port.reset_mode(self.pin);
+    }
+}
+
+impl Drop for OutputPin {
+    fn drop(&mut self) {
+        self.port.free(self.pin);
+    }
+}
+
+/// A bidirectional open-drain pin.
+///
+/// The pin either drives the line low or releases it, so that it is pulled high by a pull-up
+/// resistor or driven by another device. In both cases, the actual line level can be read.
+/// Dropping the handle gives the pin back to its port without touching its configuration.
+pub struct IoPin {
+    pin: Pin,
+    port: PortRef,
+}
+
+impl IoPin {
+    /// Returns the actual level of the line.
+    pub fn get(&self) -> bool {
+        self.port.input_data().get(self.pin)
+    }
+
+    /// Releases the line if `value` is true and drives it low otherwise.
+    pub fn set(&mut self, value: bool) {
+        self.port.set_output(self.pin, value);
+    }
+
+    /// Resets the pin mode to its reset state and gives the pin back to its port.
+    pub fn release(self) {
+        self.port.reset_mode(self.pin);
+    }
+}
+
+impl Drop for IoPin {
+    fn drop(&mut self) {
+        self.port.free(self.pin);
+    }
+}
+
+/// Several pins of the same port configured as inputs.
+///
+/// Dropping the handle gives the pins back to their port without touching their configuration.
+pub struct InputGroup {
+    pins: ArrayVec<[Pin; 16]>,
+    port: PortRef,
+}
+
+impl InputGroup {
+    /// Reads all pins from a single read of the input data register.
+    pub fn get(&self) -> u16 {
+        let input_data = self.port.input_data();
+        let mut value = 0;
+        for (i, &pin) in self.pins.iter().enumerate() {
+            if input_data.get(pin) {
+                value |= 1 << i;
+            }
+        }
+        value
+    }
+
+    /// Resets the pin modes to their reset state and gives the pins back to their port.
+    pub fn release(self) {
+        for &pin in self.pins.iter() {
+            self.port.reset_mode(pin);
+        }
     }
 }
 
+impl Drop for InputGroup {
+    fn drop(&mut self) {
+        for &pin in self.pins.iter() {
+            self.port.free(pin);
+        }
+    }
+}
+
+/// Several pins of the same port configured as outputs.
+///
+/// Dropping the handle gives the pins back to their port without touching their configuration.
+pub struct OutputGroup {
+    pins: ArrayVec<[Pin; 16]>,
+    port: PortRef,
+}
+
+impl OutputGroup {
+    /// Returns the values the pins are driven to.
+    pub fn get(&self) -> u16 {
+        let output_data = self.port.output_data();
+        let mut value = 0;
+        for (i, &pin) in self.pins.iter().enumerate() {
+            if output_data.get(pin) {
+                value |= 1 << i;
+            }
+        }
+        value
+    }
+
+    /// Sets all pins with a single write to the bit set/reset register, so that all pins change
+    /// at the same moment.
+    pub fn set(&mut self, value: u16) {
+        let mut bsrr = stm32f7::BitSetResetRegister::default();
+        for (i, &pin) in self.pins.iter().enumerate() {
+            if value & (1 << i) != 0 {
+                bsrr.set(pin);
+            } else {
+                bsrr.reset(pin);
+            }
+        }
+        self.port.write_bit_set_reset(bsrr);
+    }
+
+    /// Resets the pin modes to their reset state and gives the pins back to their port.
+    pub fn release(self) {
+        for &pin in self.pins.iter() {
+            self.port.reset_mode(pin);
+        }
+    }
+}
+
+impl Drop for OutputGroup {
+    fn drop(&mut self) {
+        for &pin in self.pins.iter() {
+            self.port.free(pin);
+        }
+    }
+}
+
+/// A pin configured for an alternate function.
+///
+/// Dropping the handle gives the pin back to its port without touching its configuration.
+pub struct AlternateFunctionPin {
+    pin: Pin,
+    port: PortRef,
+}
+
+impl AlternateFunctionPin {
+    /// Resets the pin mode to its reset state and gives the pin back to its port.
+    pub fn release(self) {
+        self.port.reset_mode(self.pin);
+    }
+}
+
+impl Drop for AlternateFunctionPin {
+    fn drop(&mut self) {
+        self.port.free(self.pin);
+    }
+}
+
+/// A pin configured for analog use.
+///
+/// ADC and DAC drivers take this handle as proof that the pin is in analog mode. Dropping the
+/// handle gives the pin back to its port without touching its configuration.
+pub struct AnalogPin {
+    pin: Pin,
+    port: PortRef,
+}
+
+impl AnalogPin {
+    /// Returns the port and number of the pin.
+    pub fn pin(&self) -> (Port, Pin) {
+        (self.port.port, self.pin)
+    }
+
+    /// Resets the pin mode to its reset state and gives the pin back to its port.
+    pub fn release(self) {
+        self.port.reset_mode(self.pin);
+    }
+}
+
+impl Drop for AnalogPin {
+    fn drop(&mut self) {
+        self.port.free(self.pin);
+    }
+}
+
+macro_rules! impl_pin_conversions {
+    ($($Handle:ident),*) => {
+        $(
+            impl $Handle {
+                /// Reconfigures the pin as input and keeps it in use.
+                ///
+                /// If the pin is locked, it is given back to its port and an error is returned.
+                pub fn into_input(self, resistor: Resistor) -> Result<InputPin, Error> {
+                    self.port.configure(self.pin,
+                                        stm32f7::Mode::Input,
+                                        OutputType::PushPull,
+                                        OutputSpeed::Low,
+                                        resistor,
+                                        AlternateFunction::AF0)?;
+                    let pin = InputPin {
+                        pin: self.pin,
+                        port: self.port.clone(),
+                    };
+                    mem::forget(self);
+                    Ok(pin)
+                }
+
+                /// Reconfigures the pin as output and keeps it in use.
+                ///
+                /// If the pin is locked, it is given back to its port and an error is returned.
+                pub fn into_output(self,
+                                   out_type: OutputType,
+                                   out_speed: OutputSpeed,
+                                   resistor: Resistor)
+                                   -> Result<OutputPin, Error> {
+                    self.port.configure(self.pin,
+                                        stm32f7::Mode::Output,
+                                        out_type,
+                                        out_speed,
+                                        resistor,
+                                        AlternateFunction::AF0)?;
+                    let pin = OutputPin {
+                        pin: self.pin,
+                        port: self.port.clone(),
+                    };
+                    mem::forget(self);
+                    Ok(pin)
+                }
+
+                /// Reconfigures the pin as bidirectional open-drain pin and keeps it in use.
+                ///
+                /// The line is released before the pin is switched to output mode. If the pin is
+                /// locked, it is given back to its port and an error is returned.
+                pub fn into_io(self,
+                               out_speed: OutputSpeed,
+                               resistor: Resistor)
+                               -> Result<IoPin, Error> {
+                    if !self.port.is_locked(self.pin) {
+                        self.port.set_output(self.pin, true);
+                    }
+                    self.port.configure(self.pin,
+                                        stm32f7::Mode::Output,
+                                        OutputType::OpenDrain,
+                                        out_speed,
+                                        resistor,
+                                        AlternateFunction::AF0)?;
+                    let pin = IoPin {
+                        pin: self.pin,
+                        port: self.port.clone(),
+                    };
+                    mem::forget(self);
+                    Ok(pin)
+                }
+
+                /// Reconfigures the pin for analog use and keeps it in use.
+                ///
+                /// If the pin is locked, it is given back to its port and an error is returned.
+                pub fn into_analog(self) -> Result<AnalogPin, Error> {
+                    self.port.configure(self.pin,
+                                        stm32f7::Mode::Analog,
+                                        OutputType::PushPull,
+                                        OutputSpeed::Low,
+                                        Resistor::NoPull,
+                                        AlternateFunction::AF0)?;
+                    let pin = AnalogPin {
+                        pin: self.pin,
+                        port: self.port.clone(),
+                    };
+                    mem::forget(self);
+                    Ok(pin)
+                }
+
+                /// Reconfigures the pin for an alternate function and keeps it in use.
+                ///
+                /// If the pin is locked, it is given back to its port and an error is returned.
+                pub fn into_alternate(self,
+                                      alternate_fn: AlternateFunction,
+                                      typ: OutputType,
+                                      speed: OutputSpeed,
+                                      resistor: Resistor)
+                                      -> Result<AlternateFunctionPin, Error> {
+                    self.port.configure(self.pin,
+                                        stm32f7::Mode::AlternateFunction,
+                                        typ,
+                                        speed,
+                                        resistor,
+                                        alternate_fn)?;
+                    let pin = AlternateFunctionPin {
+                        pin: self.pin,
+                        port: self.port.clone(),
+                    };
+                    mem::forget(self);
+                    Ok(pin)
+                }
+            }
+        )*
+    }
+}
+
+impl_pin_conversions!(InputPin, OutputPin, IoPin, AnalogPin, AlternateFunctionPin);
+
+/// A pin that carries the peripheral signal `S`, e.g. `SignalPin<signals::Usart1Tx>`.
+///
+/// Created by `Gpio::to_alternate_function_for`, which checks that the pin supports the signal.
+/// Drivers take the handle with the signal they need, so a wrong signal fails to compile.
+pub struct SignalPin<S> {
+    pin: AlternateFunctionPin,
+    _signal: PhantomData<S>,
+}
+
+impl<S: SignalType> SignalPin<S> {
+    pub fn signal(&self) -> Signal {
+        S::signal()
+    }
+
+    /// Returns the port and number of the pin.
+    pub fn pin(&self) -> (Port, Pin) {
+        (self.pin.port.port, self.pin.pin)
+    }
+
+    /// Resets the pin mode to its reset state and gives the pin back to its port.
+    pub fn release(self) {
+        self.pin.release();
+    }
+}
+
+/// Link from a port and its pin handles to the ownership bookkeeping and the port registers.
 #[derive(Debug, Clone)]
-struct BsrrRef(*mut WriteOnly<stm32f7::BitSetResetRegister>);
+struct PortRef {
+    port: Port,
+    registers: *mut stm32f7::Gpio,
+}
+
+unsafe impl Send for PortRef {}
+
+impl PortRef {
+    fn free(&mut self, pin: Pin) {
+        let port = self.port;
+        irq::free(|| unsafe { PIN_IN_USE[port as usize][pin as usize] = false });
+    }
+
+    /// Runs `f` on the register bank of the port in a critical section.
+    ///
+    /// The reference only lives for the call, so no `&mut` to the bank outlives it, and handles of
+    /// the same port can't interrupt each other's read-modify-writes of the shared registers.
+    fn update<F, R>(&self, f: F) -> R
+        where F: FnOnce(&mut stm32f7::Gpio) -> R
+    {
+        let registers = self.registers;
+        irq::free(|| unsafe { f(&mut *registers) })
+    }
+
+    /// Runs `f` on the register bank of the port for reading.
+    fn read<F, R>(&self, f: F) -> R
+        where F: FnOnce(&stm32f7::Gpio) -> R
+    {
+        unsafe { f(&*self.registers) }
+    }
 
-unsafe impl Send for BsrrRef {}
+    fn input_data(&self) -> stm32f7::InputDataRegister {
+        self.read(|gpio| gpio.input_data.read())
+    }
+
+    fn output_data(&self) -> stm32f7::OutputDataRegister {
+        self.read(|gpio| gpio.output_data.read())
+    }
 
-impl BsrrRef {
-    fn set(&self, pin: Pin, value: bool) {
+    /// Sets or resets the output of `pin` without affecting the other pins.
+    fn set_output(&self, pin: Pin, value: bool) {
         let mut bsrr = stm32f7::BitSetResetRegister::default();
         if value {
             bsrr.set(pin);
         } else {
             bsrr.reset(pin);
         }
-        unsafe { (&mut *self.0).write(bsrr) };
+        self.write_bit_set_reset(bsrr);
+    }
+
+    fn write_bit_set_reset(&self, bsrr: stm32f7::BitSetResetRegister) {
+        // the register is write-only, so a plain write through the raw pointer suffices
+        unsafe { (*self.registers).bit_set_reset.write(bsrr) };
+    }
+
+    fn is_locked(&self, pin: Pin) -> bool {
+        let lckr = self.read(|gpio| gpio.lckr.read());
+        lckr.lock_key() && lckr.locked(pin)
+    }
+
+    /// Returns what `pin` is used for, derived from its lock state and mode.
+    ///
+    /// Locked pins are reported as `Locked`, whether or not a handle owns them.
+    fn owner(&self, pin: Pin) -> Option<PinOwner> {
+        if self.is_locked(pin) {
+            return Some(PinOwner::Locked);
+        }
+        if unsafe { !PIN_IN_USE[self.port as usize][pin as usize] } {
+            return None;
+        }
+        Some(self.read(|gpio| match gpio.mode.read().get(pin) {
+            stm32f7::Mode::Input => PinOwner::Input,
+            stm32f7::Mode::Output => PinOwner::Output,
+            stm32f7::Mode::Analog => PinOwner::Analog,
+            stm32f7::Mode::AlternateFunction => {
+                PinOwner::AlternateFunction(gpio.alternate_fn.read().get(pin))
+            }
+        }))
+    }
+
+    /// Reprograms all configuration registers of a single pin.
+    ///
+    /// The alternate function is selected before the mode is changed to avoid glitches.
+    fn configure(&self,
+                 pin: Pin,
+                 mode: stm32f7::Mode,
+                 out_type: OutputType,
+                 out_speed: OutputSpeed,
+                 resistor: Resistor,
+                 alternate_fn: AlternateFunction)
+                 -> Result<(), Error> {
+        if self.is_locked(pin) {
+            return Err(Error::PinAlreadyInUse((self.port, pin), PinOwner::Locked));
+        }
+
+        self.update(|gpio| {
+            gpio.out_type.update(|r| r.set(pin, out_type));
+            gpio.out_speed.update(|r| r.set(pin, out_speed));
+            gpio.pupd.update(|r| r.set(pin, resistor));
+            gpio.alternate_fn.update(|r| r.set(pin, alternate_fn));
+            gpio.mode.update(|r| r.set(pin, mode));
+        });
+
+        Ok(())
+    }
+
+    /// Sets the mode of `pin` to the value of the mode register after reset.
+    ///
+    /// The debug pins PA13–PA15, PB3 and PB4 are in alternate function mode after reset, all
+    /// other pins are inputs. Locked pins keep their mode.
+    fn reset_mode(&self, pin: Pin) {
+        if self.is_locked(pin) {
+            return;
+        }
+        use self::Port::*;
+        let mode = match (self.port, pin as u8) {
+            (PortA, 13..=15) | (PortB, 3..=4) => stm32f7::Mode::AlternateFunction,
+            _ => stm32f7::Mode::Input,
+        };
+        self.update(|gpio| gpio.mode.update(|r| r.set(pin, mode)));
     }
 }
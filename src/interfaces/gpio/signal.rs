@@ -0,0 +1,203 @@
+//! Alternate function mapping of the STM32F7 pins to peripheral signals
+//!
+//! The table follows the alternate function mapping in the STM32F745xx/STM32F746xx datasheet.
+//! It only lists the signals of the peripherals that have a `Signal` variant, and it leaves out
+//! the pins of ports that the selected chip variant lacks.
+
+use super::{Port, Pin, AlternateFunction};
+use super::Port::*;
+use components::gpio::stm32f7::Pin::*;
+use components::gpio::stm32f7::AlternateFunction::*;
+
+/// Signal of a `SignalPin` (type state)
+///
+/// Every `Signal` has a zero-sized type of the same name in `signals`, so that peripheral drivers
+/// can require a pin with a certain signal in their signature.
+pub trait SignalType {
+    fn signal() -> Signal;
+}
+
+macro_rules! signals {
+    ($($S:ident,)*) => {
+        /// Peripheral signal that can be routed to a pin
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Signal {
+            $($S,)*
+        }
+
+        /// Signal types for `SignalPin`
+        pub mod signals {
+            use super::{Signal, SignalType};
+
+            $(
+                /// Signal (type state)
+                pub struct $S;
+
+                impl SignalType for $S {
+                    fn signal() -> Signal {
+                        Signal::$S
+                    }
+                }
+            )*
+        }
+    }
+}
+
+signals! {
+    Usart1Tx,
+    Usart1Rx,
+    Usart2Tx,
+    Usart2Rx,
+    Usart3Tx,
+    Usart3Rx,
+    Uart4Tx,
+    Uart4Rx,
+    Uart5Tx,
+    Uart5Rx,
+    Usart6Tx,
+    Usart6Rx,
+    Uart7Tx,
+    Uart7Rx,
+    I2c1Scl,
+    I2c1Sda,
+    I2c2Scl,
+    I2c2Sda,
+    I2c3Scl,
+    I2c3Sda,
+    Spi1Sck,
+    Spi1Miso,
+    Spi1Mosi,
+    Spi2Sck,
+    Spi2Miso,
+    Spi2Mosi,
+    Can1Rx,
+    Can1Tx,
+    Tim2Ch1,
+    Tim3Ch1,
+}
+
+use self::Signal::*;
+
+/// Returns the alternate function that routes `signal` to `pin`, if there is one.
+pub fn alternate_function(signal: Signal, pin: (Port, Pin)) -> Option<AlternateFunction> {
+    // the entries of ports that the chip variant lacks are left out
+    let entries = ALTERNATE_FUNCTIONS.iter();
+    #[cfg(has_gpio_e)]
+    let entries = entries.chain(ALTERNATE_FUNCTIONS_E.iter());
+    #[cfg(has_gpio_f)]
+    let entries = entries.chain(ALTERNATE_FUNCTIONS_F.iter());
+    #[cfg(has_gpio_g)]
+    let entries = entries.chain(ALTERNATE_FUNCTIONS_G.iter());
+    #[cfg(has_gpio_i)]
+    let entries = entries.chain(ALTERNATE_FUNCTIONS_I.iter());
+
+    for entry in entries {
+        if entry.0 == signal && entry.1 == pin {
+            return Some(entry.2);
+        }
+    }
+    None
+}
+
+/// Entries of the ports that every chip variant has
+static ALTERNATE_FUNCTIONS: &'static [(Signal, (Port, Pin), AlternateFunction)] = &[
+    (Usart1Tx, (PortA, Pin9), AF7),
+    (Usart1Tx, (PortB, Pin6), AF7),
+    (Usart1Rx, (PortA, Pin10), AF7),
+    (Usart1Rx, (PortB, Pin7), AF7),
+
+    (Usart2Tx, (PortA, Pin2), AF7),
+    (Usart2Tx, (PortD, Pin5), AF7),
+    (Usart2Rx, (PortA, Pin3), AF7),
+    (Usart2Rx, (PortD, Pin6), AF7),
+
+    (Usart3Tx, (PortB, Pin10), AF7),
+    (Usart3Tx, (PortC, Pin10), AF7),
+    (Usart3Tx, (PortD, Pin8), AF7),
+    (Usart3Rx, (PortB, Pin11), AF7),
+    (Usart3Rx, (PortC, Pin11), AF7),
+    (Usart3Rx, (PortD, Pin9), AF7),
+
+    (Uart4Tx, (PortA, Pin0), AF8),
+    (Uart4Tx, (PortC, Pin10), AF8),
+    (Uart4Rx, (PortA, Pin1), AF8),
+    (Uart4Rx, (PortC, Pin11), AF8),
+
+    (Uart5Tx, (PortC, Pin12), AF8),
+    (Uart5Rx, (PortD, Pin2), AF8),
+
+    (Usart6Tx, (PortC, Pin6), AF8),
+    (Usart6Rx, (PortC, Pin7), AF8),
+
+    (I2c1Scl, (PortB, Pin6), AF4),
+    (I2c1Scl, (PortB, Pin8), AF4),
+    (I2c1Sda, (PortB, Pin7), AF4),
+    (I2c1Sda, (PortB, Pin9), AF4),
+
+    (I2c2Scl, (PortB, Pin10), AF4),
+    (I2c2Scl, (PortH, Pin4), AF4),
+    (I2c2Sda, (PortB, Pin11), AF4),
+    (I2c2Sda, (PortH, Pin5), AF4),
+
+    (I2c3Scl, (PortA, Pin8), AF4),
+    (I2c3Scl, (PortH, Pin7), AF4),
+    (I2c3Sda, (PortC, Pin9), AF4),
+    (I2c3Sda, (PortH, Pin8), AF4),
+
+    (Spi1Sck, (PortA, Pin5), AF5),
+    (Spi1Sck, (PortB, Pin3), AF5),
+    (Spi1Miso, (PortA, Pin6), AF5),
+    (Spi1Miso, (PortB, Pin4), AF5),
+    (Spi1Mosi, (PortA, Pin7), AF5),
+    (Spi1Mosi, (PortB, Pin5), AF5),
+
+    (Spi2Sck, (PortB, Pin10), AF5),
+    (Spi2Sck, (PortB, Pin13), AF5),
+    (Spi2Sck, (PortD, Pin3), AF5),
+    (Spi2Miso, (PortB, Pin14), AF5),
+    (Spi2Miso, (PortC, Pin2), AF5),
+    (Spi2Mosi, (PortB, Pin15), AF5),
+    (Spi2Mosi, (PortC, Pin3), AF5),
+
+    (Can1Rx, (PortA, Pin11), AF9),
+    (Can1Rx, (PortB, Pin8), AF9),
+    (Can1Rx, (PortD, Pin0), AF9),
+    (Can1Tx, (PortA, Pin12), AF9),
+    (Can1Tx, (PortB, Pin9), AF9),
+    (Can1Tx, (PortD, Pin1), AF9),
+
+    (Tim2Ch1, (PortA, Pin0), AF1),
+    (Tim2Ch1, (PortA, Pin5), AF1),
+    (Tim2Ch1, (PortA, Pin15), AF1),
+    (Tim3Ch1, (PortA, Pin6), AF2),
+    (Tim3Ch1, (PortB, Pin4), AF2),
+    (Tim3Ch1, (PortC, Pin6), AF2),
+];
+
+#[cfg(has_gpio_e)]
+static ALTERNATE_FUNCTIONS_E: &'static [(Signal, (Port, Pin), AlternateFunction)] = &[
+    (Uart7Tx, (PortE, Pin8), AF8),
+    (Uart7Rx, (PortE, Pin7), AF8),
+];
+
+#[cfg(has_gpio_f)]
+static ALTERNATE_FUNCTIONS_F: &'static [(Signal, (Port, Pin), AlternateFunction)] = &[
+    (Uart7Tx, (PortF, Pin7), AF8),
+    (Uart7Rx, (PortF, Pin6), AF8),
+
+    (I2c2Scl, (PortF, Pin1), AF4),
+    (I2c2Sda, (PortF, Pin0), AF4),
+];
+
+#[cfg(has_gpio_g)]
+static ALTERNATE_FUNCTIONS_G: &'static [(Signal, (Port, Pin), AlternateFunction)] = &[
+    (Usart6Tx, (PortG, Pin14), AF8),
+    (Usart6Rx, (PortG, Pin9), AF8),
+];
+
+#[cfg(has_gpio_i)]
+static ALTERNATE_FUNCTIONS_I: &'static [(Signal, (Port, Pin), AlternateFunction)] = &[
+    (Spi2Sck, (PortI, Pin1), AF5),
+    (Spi2Miso, (PortI, Pin2), AF5),
+    (Spi2Mosi, (PortI, Pin3), AF5),
+];
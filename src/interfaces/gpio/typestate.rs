@@ -0,0 +1,519 @@
+//! Typestate GPIO pins
+//!
+//! `Gpio::split` turns the GPIO interface into one zero-sized type per pin. The type parameter
+//! of a pin encodes its current mode, e.g. `PA9<Input<PullUp>>`. Mode changes consume the pin
+//! and return it with its new type, so using a pin twice or in the wrong mode fails to compile.
+//!
+//! The pins access the registers of their port through the raw pointers that `split` takes over
+//! from the consumed `Gpio`, using the setters of `components::gpio::stm32f7`.
+//!
+//! Pins locked through `GpioPort::lock_pins` can't be reconfigured until the next reset, so
+//! `split` leaves them out instead of handing out types that don't match the hardware.
+
+use core::marker::PhantomData;
+use components::gpio::stm32f7;
+use irq;
+use super::{Gpio, Port, Pin, OutputType, OutputSpeed, Resistor, AlternateFunction, Error};
+
+/// Input mode (type state)
+pub struct Input<PULL> {
+    _pull: PhantomData<PULL>,
+}
+
+/// Input without pull resistor (type state)
+pub struct Floating;
+
+/// Input with pull-up resistor (type state)
+pub struct PullUp;
+
+/// Input with pull-down resistor (type state)
+pub struct PullDown;
+
+/// Output mode (type state)
+pub struct Output<OTYPE> {
+    _otype: PhantomData<OTYPE>,
+}
+
+/// Push-pull output (type state)
+pub struct PushPull;
+
+/// Open-drain output (type state)
+pub struct OpenDrain;
+
+/// Analog mode (type state)
+pub struct Analog;
+
+/// Alternate function mode (type state)
+pub struct Alternate<AF, OTYPE> {
+    _af: PhantomData<AF>,
+    _otype: PhantomData<OTYPE>,
+}
+
+/// Debug port mode of PA13–PA15, PB3 and PB4 after reset (type state)
+pub struct Debugger;
+
+/// Pull resistor setting of an input type state.
+pub trait PullResistor {
+    fn resistor() -> Resistor;
+}
+
+impl PullResistor for Floating {
+    fn resistor() -> Resistor {
+        Resistor::NoPull
+    }
+}
+
+impl PullResistor for PullUp {
+    fn resistor() -> Resistor {
+        Resistor::PullUp
+    }
+}
+
+impl PullResistor for PullDown {
+    fn resistor() -> Resistor {
+        Resistor::PullDown
+    }
+}
+
+/// Output type setting of an output type state.
+pub trait OutputTypeState {
+    fn output_type() -> OutputType;
+}
+
+impl OutputTypeState for PushPull {
+    fn output_type() -> OutputType {
+        OutputType::PushPull
+    }
+}
+
+impl OutputTypeState for OpenDrain {
+    fn output_type() -> OutputType {
+        OutputType::OpenDrain
+    }
+}
+
+/// Alternate function number of an alternate function type state.
+pub trait AlternateFunctionNumber {
+    fn alternate_function() -> AlternateFunction;
+}
+
+macro_rules! alternate_functions {
+    ($($AF:ident,)*) => {
+        $(
+            /// Alternate function number (type state)
+            pub struct $AF;
+
+            impl AlternateFunctionNumber for $AF {
+                fn alternate_function() -> AlternateFunction {
+                    AlternateFunction::$AF
+                }
+            }
+        )*
+    }
+}
+
+alternate_functions! {
+    AF0, AF1, AF2, AF3, AF4, AF5, AF6, AF7, AF8, AF9, AF10, AF11, AF12, AF13, AF14, AF15,
+}
+
+/// Register banks of ports A to K, indexed by `Port`, as taken over by `Gpio::split`.
+///
+/// The pins are zero-sized, so they look up their bank here. Pins of the same port share the
+/// configuration registers, so all read-modify-writes happen in `irq::free` critical sections.
+static mut REGISTERS: [*mut stm32f7::Gpio; 11] = [0 as *mut stm32f7::Gpio; 11];
+
+fn registers(port: Port) -> *mut stm32f7::Gpio {
+    unsafe { REGISTERS[port as usize] }
+}
+
+fn set_mode(port: Port,
+            pin: Pin,
+            mode: stm32f7::Mode,
+            out_type: OutputType,
+            resistor: Resistor) {
+    let gpio = registers(port);
+    irq::free(|| unsafe {
+        (*gpio).out_type.update(|r| r.set(pin, out_type));
+        (*gpio).pupd.update(|r| r.set(pin, resistor));
+        (*gpio).mode.update(|r| r.set(pin, mode));
+    });
+}
+
+fn set_alternate_function(port: Port, pin: Pin, alternate_fn: AlternateFunction) {
+    let gpio = registers(port);
+    irq::free(|| unsafe { (*gpio).alternate_fn.update(|r| r.set(pin, alternate_fn)) });
+}
+
+fn set_speed(port: Port, pin: Pin, speed: OutputSpeed) {
+    let gpio = registers(port);
+    irq::free(|| unsafe { (*gpio).out_speed.update(|r| r.set(pin, speed)) });
+}
+
+fn is_locked(port: Port, pin: Pin) -> bool {
+    let lckr = unsafe { (*registers(port)).lckr.read() };
+    lckr.lock_key() && lckr.locked(pin)
+}
+
+fn input(port: Port, pin: Pin) -> bool {
+    unsafe { (*registers(port)).input_data.read().get(pin) }
+}
+
+fn output(port: Port, pin: Pin) -> bool {
+    unsafe { (*registers(port)).output_data.read().get(pin) }
+}
+
+fn set_output(port: Port, pin: Pin, value: bool) {
+    let mut bsrr = stm32f7::BitSetResetRegister::default();
+    if value {
+        bsrr.set(pin);
+    } else {
+        bsrr.reset(pin);
+    }
+    // the bit set/reset register is write-only, so no critical section is needed
+    unsafe { (*registers(port)).bit_set_reset.write(bsrr) };
+}
+
+macro_rules! pins {
+    ($($Port:ident ($cfg:meta): [$($PXi:ident: ($pxi:ident, $Pin:ident, $MODE:ty),)*],)*) => {
+        /// All pins of all ports in their state after reset.
+        ///
+        /// Locked pins are `None`.
+        pub struct Pins {
+            $($(
+                #[cfg($cfg)]
+                pub $pxi: Option<$PXi<$MODE>>,
+            )*)*
+        }
+
+        impl Gpio {
+            /// Splits the interface into one zero-sized type per pin.
+            ///
+            /// The returned types assume that the pins are in their reset configuration, so this
+            /// should be called before any pin is configured. All pins are marked as in use, so
+            /// that no `GpioPort` can hand them out again. Fails if a pin handle is still alive,
+            /// since its pin would be handed out twice. Locked pins are left out, since their
+            /// configuration can't change.
+            pub fn split(mut self) -> Result<Pins, Error> {
+                self.use_all_pins()?;
+                for port in self.ports() {
+                    unsafe { REGISTERS[port.port.port as usize] = port.port.registers };
+                }
+
+                Ok(Pins {
+                    $($(
+                        #[cfg($cfg)]
+                        $pxi: if is_locked(Port::$Port, Pin::$Pin) {
+                            None
+                        } else {
+                            Some($PXi { _mode: PhantomData })
+                        },
+                    )*)*
+                })
+            }
+        }
+
+        $($(
+            /// Pin (zero-sized, the type parameter is its mode)
+            #[cfg($cfg)]
+            pub struct $PXi<MODE> {
+                _mode: PhantomData<MODE>,
+            }
+
+            #[cfg($cfg)]
+            impl<MODE> $PXi<MODE> {
+                /// Configures the pin as input without pull resistor.
+                pub fn into_floating_input(self) -> $PXi<Input<Floating>> {
+                    self.into_input()
+                }
+
+                /// Configures the pin as input with pull-up resistor.
+                pub fn into_pull_up_input(self) -> $PXi<Input<PullUp>> {
+                    self.into_input()
+                }
+
+                /// Configures the pin as input with pull-down resistor.
+                pub fn into_pull_down_input(self) -> $PXi<Input<PullDown>> {
+                    self.into_input()
+                }
+
+                /// Configures the pin as input with the pull resistor given by `PULL`.
+                pub fn into_input<PULL: PullResistor>(self) -> $PXi<Input<PULL>> {
+                    set_mode(Port::$Port,
+                             Pin::$Pin,
+                             stm32f7::Mode::Input,
+                             OutputType::PushPull,
+                             PULL::resistor());
+                    $PXi { _mode: PhantomData }
+                }
+
+                /// Configures the pin as push-pull output.
+                pub fn into_push_pull_output(self) -> $PXi<Output<PushPull>> {
+                    self.into_output()
+                }
+
+                /// Configures the pin as open-drain output.
+                pub fn into_open_drain_output(self) -> $PXi<Output<OpenDrain>> {
+                    self.into_output()
+                }
+
+                /// Configures the pin as output with the output type given by `OTYPE`.
+                pub fn into_output<OTYPE: OutputTypeState>(self) -> $PXi<Output<OTYPE>> {
+                    set_mode(Port::$Port,
+                             Pin::$Pin,
+                             stm32f7::Mode::Output,
+                             OTYPE::output_type(),
+                             Resistor::NoPull);
+                    $PXi { _mode: PhantomData }
+                }
+
+                /// Configures the pin for analog use and disables its pull resistors.
+                pub fn into_analog(self) -> $PXi<Analog> {
+                    set_mode(Port::$Port,
+                             Pin::$Pin,
+                             stm32f7::Mode::Analog,
+                             OutputType::PushPull,
+                             Resistor::NoPull);
+                    $PXi { _mode: PhantomData }
+                }
+
+                /// Configures the pin for the alternate function `AF` with the output type
+                /// given by `OTYPE`.
+                pub fn into_alternate<AF, OTYPE>(self) -> $PXi<Alternate<AF, OTYPE>>
+                    where AF: AlternateFunctionNumber,
+                          OTYPE: OutputTypeState
+                {
+                    // select the function before switching the mode to avoid glitches
+                    set_alternate_function(Port::$Port, Pin::$Pin, AF::alternate_function());
+                    set_mode(Port::$Port,
+                             Pin::$Pin,
+                             stm32f7::Mode::AlternateFunction,
+                             OTYPE::output_type(),
+                             Resistor::NoPull);
+                    $PXi { _mode: PhantomData }
+                }
+            }
+
+            #[cfg($cfg)]
+            impl<PULL> $PXi<Input<PULL>> {
+                pub fn get(&self) -> bool {
+                    input(Port::$Port, Pin::$Pin)
+                }
+            }
+
+            #[cfg($cfg)]
+            impl<OTYPE> $PXi<Output<OTYPE>> {
+                pub fn get(&self) -> bool {
+                    output(Port::$Port, Pin::$Pin)
+                }
+
+                pub fn set(&mut self, value: bool) {
+                    set_output(Port::$Port, Pin::$Pin, value);
+                }
+
+                pub fn set_speed(&mut self, speed: OutputSpeed) {
+                    set_speed(Port::$Port, Pin::$Pin, speed);
+                }
+            }
+
+            #[cfg($cfg)]
+            impl<AF, OTYPE> $PXi<Alternate<AF, OTYPE>> {
+                pub fn set_speed(&mut self, speed: OutputSpeed) {
+                    set_speed(Port::$Port, Pin::$Pin, speed);
+                }
+            }
+        )*)*
+    }
+}
+
+pins! {
+    PortA (all()): [
+        PA0: (pa0, Pin0, Input<Floating>),
+        PA1: (pa1, Pin1, Input<Floating>),
+        PA2: (pa2, Pin2, Input<Floating>),
+        PA3: (pa3, Pin3, Input<Floating>),
+        PA4: (pa4, Pin4, Input<Floating>),
+        PA5: (pa5, Pin5, Input<Floating>),
+        PA6: (pa6, Pin6, Input<Floating>),
+        PA7: (pa7, Pin7, Input<Floating>),
+        PA8: (pa8, Pin8, Input<Floating>),
+        PA9: (pa9, Pin9, Input<Floating>),
+        PA10: (pa10, Pin10, Input<Floating>),
+        PA11: (pa11, Pin11, Input<Floating>),
+        PA12: (pa12, Pin12, Input<Floating>),
+        PA13: (pa13, Pin13, Debugger),
+        PA14: (pa14, Pin14, Debugger),
+        PA15: (pa15, Pin15, Debugger),
+    ],
+    PortB (all()): [
+        PB0: (pb0, Pin0, Input<Floating>),
+        PB1: (pb1, Pin1, Input<Floating>),
+        PB2: (pb2, Pin2, Input<Floating>),
+        PB3: (pb3, Pin3, Debugger),
+        PB4: (pb4, Pin4, Debugger),
+        PB5: (pb5, Pin5, Input<Floating>),
+        PB6: (pb6, Pin6, Input<Floating>),
+        PB7: (pb7, Pin7, Input<Floating>),
+        PB8: (pb8, Pin8, Input<Floating>),
+        PB9: (pb9, Pin9, Input<Floating>),
+        PB10: (pb10, Pin10, Input<Floating>),
+        PB11: (pb11, Pin11, Input<Floating>),
+        PB12: (pb12, Pin12, Input<Floating>),
+        PB13: (pb13, Pin13, Input<Floating>),
+        PB14: (pb14, Pin14, Input<Floating>),
+        PB15: (pb15, Pin15, Input<Floating>),
+    ],
+    PortC (all()): [
+        PC0: (pc0, Pin0, Input<Floating>),
+        PC1: (pc1, Pin1, Input<Floating>),
+        PC2: (pc2, Pin2, Input<Floating>),
+        PC3: (pc3, Pin3, Input<Floating>),
+        PC4: (pc4, Pin4, Input<Floating>),
+        PC5: (pc5, Pin5, Input<Floating>),
+        PC6: (pc6, Pin6, Input<Floating>),
+        PC7: (pc7, Pin7, Input<Floating>),
+        PC8: (pc8, Pin8, Input<Floating>),
+        PC9: (pc9, Pin9, Input<Floating>),
+        PC10: (pc10, Pin10, Input<Floating>),
+        PC11: (pc11, Pin11, Input<Floating>),
+        PC12: (pc12, Pin12, Input<Floating>),
+        PC13: (pc13, Pin13, Input<Floating>),
+        PC14: (pc14, Pin14, Input<Floating>),
+        PC15: (pc15, Pin15, Input<Floating>),
+    ],
+    PortD (all()): [
+        PD0: (pd0, Pin0, Input<Floating>),
+        PD1: (pd1, Pin1, Input<Floating>),
+        PD2: (pd2, Pin2, Input<Floating>),
+        PD3: (pd3, Pin3, Input<Floating>),
+        PD4: (pd4, Pin4, Input<Floating>),
+        PD5: (pd5, Pin5, Input<Floating>),
+        PD6: (pd6, Pin6, Input<Floating>),
+        PD7: (pd7, Pin7, Input<Floating>),
+        PD8: (pd8, Pin8, Input<Floating>),
+        PD9: (pd9, Pin9, Input<Floating>),
+        PD10: (pd10, Pin10, Input<Floating>),
+        PD11: (pd11, Pin11, Input<Floating>),
+        PD12: (pd12, Pin12, Input<Floating>),
+        PD13: (pd13, Pin13, Input<Floating>),
+        PD14: (pd14, Pin14, Input<Floating>),
+        PD15: (pd15, Pin15, Input<Floating>),
+    ],
+    PortE (has_gpio_e): [
+        PE0: (pe0, Pin0, Input<Floating>),
+        PE1: (pe1, Pin1, Input<Floating>),
+        PE2: (pe2, Pin2, Input<Floating>),
+        PE3: (pe3, Pin3, Input<Floating>),
+        PE4: (pe4, Pin4, Input<Floating>),
+        PE5: (pe5, Pin5, Input<Floating>),
+        PE6: (pe6, Pin6, Input<Floating>),
+        PE7: (pe7, Pin7, Input<Floating>),
+        PE8: (pe8, Pin8, Input<Floating>),
+        PE9: (pe9, Pin9, Input<Floating>),
+        PE10: (pe10, Pin10, Input<Floating>),
+        PE11: (pe11, Pin11, Input<Floating>),
+        PE12: (pe12, Pin12, Input<Floating>),
+        PE13: (pe13, Pin13, Input<Floating>),
+        PE14: (pe14, Pin14, Input<Floating>),
+        PE15: (pe15, Pin15, Input<Floating>),
+    ],
+    PortF (has_gpio_f): [
+        PF0: (pf0, Pin0, Input<Floating>),
+        PF1: (pf1, Pin1, Input<Floating>),
+        PF2: (pf2, Pin2, Input<Floating>),
+        PF3: (pf3, Pin3, Input<Floating>),
+        PF4: (pf4, Pin4, Input<Floating>),
+        PF5: (pf5, Pin5, Input<Floating>),
+        PF6: (pf6, Pin6, Input<Floating>),
+        PF7: (pf7, Pin7, Input<Floating>),
+        PF8: (pf8, Pin8, Input<Floating>),
+        PF9: (pf9, Pin9, Input<Floating>),
+        PF10: (pf10, Pin10, Input<Floating>),
+        PF11: (pf11, Pin11, Input<Floating>),
+        PF12: (pf12, Pin12, Input<Floating>),
+        PF13: (pf13, Pin13, Input<Floating>),
+        PF14: (pf14, Pin14, Input<Floating>),
+        PF15: (pf15, Pin15, Input<Floating>),
+    ],
+    PortG (has_gpio_g): [
+        PG0: (pg0, Pin0, Input<Floating>),
+        PG1: (pg1, Pin1, Input<Floating>),
+        PG2: (pg2, Pin2, Input<Floating>),
+        PG3: (pg3, Pin3, Input<Floating>),
+        PG4: (pg4, Pin4, Input<Floating>),
+        PG5: (pg5, Pin5, Input<Floating>),
+        PG6: (pg6, Pin6, Input<Floating>),
+        PG7: (pg7, Pin7, Input<Floating>),
+        PG8: (pg8, Pin8, Input<Floating>),
+        PG9: (pg9, Pin9, Input<Floating>),
+        PG10: (pg10, Pin10, Input<Floating>),
+        PG11: (pg11, Pin11, Input<Floating>),
+        PG12: (pg12, Pin12, Input<Floating>),
+        PG13: (pg13, Pin13, Input<Floating>),
+        PG14: (pg14, Pin14, Input<Floating>),
+        PG15: (pg15, Pin15, Input<Floating>),
+    ],
+    PortH (all()): [
+        PH0: (ph0, Pin0, Input<Floating>),
+        PH1: (ph1, Pin1, Input<Floating>),
+        PH2: (ph2, Pin2, Input<Floating>),
+        PH3: (ph3, Pin3, Input<Floating>),
+        PH4: (ph4, Pin4, Input<Floating>),
+        PH5: (ph5, Pin5, Input<Floating>),
+        PH6: (ph6, Pin6, Input<Floating>),
+        PH7: (ph7, Pin7, Input<Floating>),
+        PH8: (ph8, Pin8, Input<Floating>),
+        PH9: (ph9, Pin9, Input<Floating>),
+        PH10: (ph10, Pin10, Input<Floating>),
+        PH11: (ph11, Pin11, Input<Floating>),
+        PH12: (ph12, Pin12, Input<Floating>),
+        PH13: (ph13, Pin13, Input<Floating>),
+        PH14: (ph14, Pin14, Input<Floating>),
+        PH15: (ph15, Pin15, Input<Floating>),
+    ],
+    PortI (has_gpio_i): [
+        PI0: (pi0, Pin0, Input<Floating>),
+        PI1: (pi1, Pin1, Input<Floating>),
+        PI2: (pi2, Pin2, Input<Floating>),
+        PI3: (pi3, Pin3, Input<Floating>),
+        PI4: (pi4, Pin4, Input<Floating>),
+        PI5: (pi5, Pin5, Input<Floating>),
+        PI6: (pi6, Pin6, Input<Floating>),
+        PI7: (pi7, Pin7, Input<Floating>),
+        PI8: (pi8, Pin8, Input<Floating>),
+        PI9: (pi9, Pin9, Input<Floating>),
+        PI10: (pi10, Pin10, Input<Floating>),
+        PI11: (pi11, Pin11, Input<Floating>),
+        PI12: (pi12, Pin12, Input<Floating>),
+        PI13: (pi13, Pin13, Input<Floating>),
+        PI14: (pi14, Pin14, Input<Floating>),
+        PI15: (pi15, Pin15, Input<Floating>),
+    ],
+    PortJ (has_gpio_j): [
+        PJ0: (pj0, Pin0, Input<Floating>),
+        PJ1: (pj1, Pin1, Input<Floating>),
+        PJ2: (pj2, Pin2, Input<Floating>),
+        PJ3: (pj3, Pin3, Input<Floating>),
+        PJ4: (pj4, Pin4, Input<Floating>),
+        PJ5: (pj5, Pin5, Input<Floating>),
+        PJ6: (pj6, Pin6, Input<Floating>),
+        PJ7: (pj7, Pin7, Input<Floating>),
+        PJ8: (pj8, Pin8, Input<Floating>),
+        PJ9: (pj9, Pin9, Input<Floating>),
+        PJ10: (pj10, Pin10, Input<Floating>),
+        PJ11: (pj11, Pin11, Input<Floating>),
+        PJ12: (pj12, Pin12, Input<Floating>),
+        PJ13: (pj13, Pin13, Input<Floating>),
+        PJ14: (pj14, Pin14, Input<Floating>),
+        PJ15: (pj15, Pin15, Input<Floating>),
+    ],
+    PortK (has_gpio_k): [
+        PK0: (pk0, Pin0, Input<Floating>),
+        PK1: (pk1, Pin1, Input<Floating>),
+        PK2: (pk2, Pin2, Input<Floating>),
+        PK3: (pk3, Pin3, Input<Floating>),
+        PK4: (pk4, Pin4, Input<Floating>),
+        PK5: (pk5, Pin5, Input<Floating>),
+        PK6: (pk6, Pin6, Input<Floating>),
+        PK7: (pk7, Pin7, Input<Floating>),
+    ],
+}
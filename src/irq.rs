@@ -34,6 +34,39 @@ pub unsafe fn disable_fault_irq() {
     asm!("CPSID f" : : : : "volatile");
 }
 
+/// Returns whether IRQs are disabled (PRIMASK is set)
+#[cfg(target_arch = "arm")]
+fn primask() -> bool {
+    let primask: u32;
+    unsafe { asm!("MRS $0, PRIMASK" : "=r"(primask) : : : "volatile") };
+    primask & 1 == 1
+}
+
+/// Runs `f` with IRQs disabled, e.g. for a read-modify-write of shared state.
+///
+/// IRQs are only enabled again if they were enabled before, so critical sections can nest.
+/// NMI and HardFault are not masked.
+#[cfg(target_arch = "arm")]
+pub fn free<F, R>(f: F) -> R
+    where F: FnOnce() -> R
+{
+    let masked = primask();
+    unsafe { disable_irq() };
+    let result = f();
+    if !masked {
+        unsafe { enable_irq() };
+    }
+    result
+}
+
+/// Runs `f`; without IRQs to mask on other architectures, e.g. in host tests.
+#[cfg(not(target_arch = "arm"))]
+pub fn free<F, R>(f: F) -> R
+    where F: FnOnce() -> R
+{
+    f()
+}
+
 pub trait MaskRegister {
     fn get_mask(&self) -> bool;
     fn set_mask(&mut self, enabled: bool);
@@ -78,7 +111,7 @@ impl<'a, R: MaskRegister, T> MaskMutex<R, T> {
     }
 
     pub fn into_inner(self) -> T {
-        unsafe { self.data.into_inner() }
+        self.data.into_inner()
     }
 }
 
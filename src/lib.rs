@@ -3,21 +3,35 @@
 //! See the `README.md` for a detailed introduction.
 
 #![feature(asm)]
-#![feature(lang_items)]
 #![feature(unwind_attributes)]
 #![no_std]
+// the crate predates field init shorthand and elided lifetimes in statics; registers are read
+// through `&self`, flags are written as `1 << n`, and pins are converted with `to_*(self)`
+#![allow(clippy::redundant_field_names,
+         clippy::redundant_static_lifetimes,
+         clippy::trivially_copy_pass_by_ref,
+         clippy::identity_op,
+         clippy::wrong_self_convention,
+         clippy::cast_lossless,
+         clippy::range_plus_one)]
 
 #[macro_use]
 extern crate bitflags;
 extern crate bit_field;
 extern crate volatile;
 extern crate arrayvec;
+#[cfg(feature = "embedded-hal")]
+extern crate embedded_hal;
 
+pub mod boards;
 pub mod components;
 pub mod interfaces;
 pub mod irq;
+pub mod peripherals;
 pub mod util;
 pub mod runtime;
+#[cfg(feature = "embedded-hal")]
+mod hal;
 
 pub type InterruptHandler = extern "C" fn() -> ();
 
@@ -42,7 +56,10 @@ macro_rules! board {
         }
 
         extern "C" fn _rust_start() {
-            ::main(unsafe { $crate::boards::$board::hw() })
+            unsafe {
+                $crate::runtime::record_reset_causes();
+                ::main($crate::boards::$board::hw())
+            }
         }
 
         #[link_section="vectors"]
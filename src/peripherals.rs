@@ -0,0 +1,99 @@
+//! Singleton access to the register banks
+//!
+//! `Peripherals::take` hands out a reference to every register bank at its fixed address, but
+//! only once. This way, safe code can never create two references to the same registers.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use components::rcc::RccBank;
+use components::systick::SysTickBank;
+use components::pwr::PwrBank;
+use components::flash::FlashBank;
+use components::syscfg::SyscfgBank;
+use components::exti::ExtiBank;
+use components::gpio::stm32f7;
+
+/// Address of the RCC registers, also used by the startup code before the banks are taken
+pub(crate) const RCC_ADDRESS: usize = 0x4002_3800;
+
+static TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// All register banks of the STM32F7
+pub struct Peripherals {
+    pub rcc: &'static mut RccBank,
+    pub systick: &'static mut SysTickBank,
+    pub pwr: &'static mut PwrBank,
+    pub flash: &'static mut FlashBank,
+    pub syscfg: &'static mut SyscfgBank,
+    pub exti: &'static mut ExtiBank,
+    pub gpio: GpioBanks,
+}
+
+/// The register banks of GPIO ports A to K, as expected by `interfaces::gpio::Gpio::new`
+///
+/// Only the ports of the selected chip and package features exist.
+pub struct GpioBanks {
+    pub gpio_a: &'static mut stm32f7::Gpio,
+    pub gpio_b: &'static mut stm32f7::Gpio,
+    pub gpio_c: &'static mut stm32f7::Gpio,
+    pub gpio_d: &'static mut stm32f7::Gpio,
+    #[cfg(has_gpio_e)]
+    pub gpio_e: &'static mut stm32f7::Gpio,
+    #[cfg(has_gpio_f)]
+    pub gpio_f: &'static mut stm32f7::Gpio,
+    #[cfg(has_gpio_g)]
+    pub gpio_g: &'static mut stm32f7::Gpio,
+    pub gpio_h: &'static mut stm32f7::Gpio,
+    #[cfg(has_gpio_i)]
+    pub gpio_i: &'static mut stm32f7::Gpio,
+    #[cfg(has_gpio_j)]
+    pub gpio_j: &'static mut stm32f7::Gpio,
+    #[cfg(has_gpio_k)]
+    pub gpio_k: &'static mut stm32f7::Gpio,
+}
+
+impl Peripherals {
+    /// Returns all register banks on the first call and `None` on all later calls.
+    pub fn take() -> Option<Peripherals> {
+        if TAKEN.swap(true, Ordering::SeqCst) {
+            None
+        } else {
+            Some(unsafe { Peripherals::steal() })
+        }
+    }
+
+    /// Returns all register banks, regardless of whether they were already taken.
+    ///
+    /// This is meant for interrupt handlers that need to access registers owned by the main
+    /// program. It is unsafe because it creates aliasing mutable references.
+    pub unsafe fn steal() -> Peripherals {
+        TAKEN.store(true, Ordering::SeqCst);
+
+        Peripherals {
+            rcc: &mut *(RCC_ADDRESS as *mut RccBank),
+            systick: &mut *(0xe000_e010 as *mut SysTickBank),
+            pwr: &mut *(0x4000_7000 as *mut PwrBank),
+            flash: &mut *(0x4002_3c00 as *mut FlashBank),
+            syscfg: &mut *(0x4001_3800 as *mut SyscfgBank),
+            exti: &mut *(0x4001_3c00 as *mut ExtiBank),
+            gpio: GpioBanks {
+                gpio_a: &mut *(0x4002_0000 as *mut stm32f7::Gpio),
+                gpio_b: &mut *(0x4002_0400 as *mut stm32f7::Gpio),
+                gpio_c: &mut *(0x4002_0800 as *mut stm32f7::Gpio),
+                gpio_d: &mut *(0x4002_0c00 as *mut stm32f7::Gpio),
+                #[cfg(has_gpio_e)]
+                gpio_e: &mut *(0x4002_1000 as *mut stm32f7::Gpio),
+                #[cfg(has_gpio_f)]
+                gpio_f: &mut *(0x4002_1400 as *mut stm32f7::Gpio),
+                #[cfg(has_gpio_g)]
+                gpio_g: &mut *(0x4002_1800 as *mut stm32f7::Gpio),
+                gpio_h: &mut *(0x4002_1c00 as *mut stm32f7::Gpio),
+                #[cfg(has_gpio_i)]
+                gpio_i: &mut *(0x4002_2000 as *mut stm32f7::Gpio),
+                #[cfg(has_gpio_j)]
+                gpio_j: &mut *(0x4002_2400 as *mut stm32f7::Gpio),
+                #[cfg(has_gpio_k)]
+                gpio_k: &mut *(0x4002_2800 as *mut stm32f7::Gpio),
+            },
+        }
+    }
+}
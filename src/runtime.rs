@@ -1,12 +1,35 @@
 // provisional runtime
-// apparently, even with panic="abort" you need to have a panic_fmt
-// implementation
-#[cfg(feature = "panic-fmt")]
-use core::fmt;
+// apparently, even with panic="abort" you need to have a panic handler
+#[cfg(all(feature = "panic-fmt", not(test)))]
+use core::panic::PanicInfo;
+use components::rcc::{RccBank, ResetCauses};
+use peripherals::RCC_ADDRESS;
 
-#[cfg(feature = "panic-fmt")]
-#[lang = "panic_fmt"]
-extern "C" fn panic_impl(_: fmt::Arguments, _: &'static str, _: u32) -> ! {
+/// Reset flags read at startup
+static mut RESET_CAUSES: Option<ResetCauses> = None;
+
+/// Reads and clears the reset flags, so that `reset_causes` can return them.
+///
+/// The reset handler of the `board!` macro calls this before it takes the peripherals and runs
+/// `main`. It is unsafe because it accesses the RCC registers without owning them, so it must not
+/// run once the peripherals are taken.
+pub unsafe fn record_reset_causes() {
+    let rcc = &mut *(RCC_ADDRESS as *mut RccBank);
+    RESET_CAUSES = Some(rcc.reset_cause());
+}
+
+/// Returns why the device was reset, or `None` if the startup code didn't record it.
+///
+/// The flags are cleared when they are recorded, so `RccBank::reset_cause` returns none after
+/// that.
+pub fn reset_causes() -> Option<ResetCauses> {
+    unsafe { RESET_CAUSES }
+}
+
+#[cfg(all(feature = "panic-fmt", not(test)))]
+#[panic_handler]
+#[allow(clippy::empty_loop)]
+fn panic_impl(_: &PanicInfo) -> ! {
     loop {}
 }
 